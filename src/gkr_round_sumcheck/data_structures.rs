@@ -1,21 +1,156 @@
 //! Data structures used by GKR Round Sumcheck
 
 use crate::ml_sumcheck::protocol::prover::ProverMsg;
+use crate::ml_sumcheck::protocol::verifier::{interpolate_uni_poly, SubClaim};
+use crate::ml_sumcheck::protocol::IPForMLSumcheck;
+use crate::rng::FeedableRNG;
 use ark_ff::Field;
 use ark_poly::{DenseMultilinearExtension, Polynomial, SparseMultilinearExtension};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::vec::Vec;
 
 /// Proof for GKR Round Function
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct GKRProof<F: Field> {
     pub(crate) phase1_sumcheck_msgs: Vec<ProverMsg<F>>,
     pub(crate) phase2_sumcheck_msgs: Vec<ProverMsg<F>>,
+    /// claimed evaluation of f2 at u (the product of all factors on x for a generalized function)
+    pub(crate) f2_u: F,
+    /// claimed evaluation of f3 at v (the product of all factors on y for a generalized function)
+    pub(crate) f3_v: F,
 }
 
 impl<F: Field> GKRProof<F> {
     /// Extract the witness (i.e. the sum of GKR)
-    pub fn extract_sum(&self) -> F {
-        self.phase1_sumcheck_msgs[0].evaluations[0] + self.phase1_sumcheck_msgs[0].evaluations[1]
+    ///
+    /// Returns `Error::Reject` if the proof has no first round message or the message has fewer
+    /// than two evaluations.
+    pub fn extract_sum(&self) -> Result<F, crate::Error> {
+        match self.phase1_sumcheck_msgs.first() {
+            Some(msg) if msg.evaluations.len() >= 2 => Ok(msg.evaluations[0] + msg.evaluations[1]),
+            _ => Err(crate::Error::Reject(Some(
+                "Proof has no well-formed first round message.".into(),
+            ))),
+        }
     }
+
+    /// The claimed evaluation of f2 at the phase one point u
+    pub fn f2_u(&self) -> F {
+        self.f2_u
+    }
+
+    /// The claimed evaluation of f3 at the phase two point v
+    pub fn f3_v(&self) -> F {
+        self.f3_v
+    }
+
+    /// Omit the evaluation `P(0)` from each round message. The verifier recovers it as the claim
+    /// of the round minus `P(1)`, so the compressed proof carries the same information.
+    pub fn compress(&self) -> CompressedGKRProof<F> {
+        let compress_phase = |msgs: &[ProverMsg<F>]| {
+            msgs.iter()
+                .map(|msg| ProverMsg {
+                    evaluations: msg.evaluations[1..].to_vec(),
+                })
+                .collect()
+        };
+        CompressedGKRProof {
+            phase1_sumcheck_msgs: compress_phase(&self.phase1_sumcheck_msgs),
+            phase2_sumcheck_msgs: compress_phase(&self.phase2_sumcheck_msgs),
+            f2_u: self.f2_u,
+            f3_v: self.f3_v,
+        }
+    }
+}
+
+/// Proof for GKR Round Function generated by `GKRProof::compress`, where the evaluation `P(0)` is
+/// omitted from each round message
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CompressedGKRProof<F: Field> {
+    pub(crate) phase1_sumcheck_msgs: Vec<ProverMsg<F>>,
+    pub(crate) phase2_sumcheck_msgs: Vec<ProverMsg<F>>,
+    pub(crate) f2_u: F,
+    pub(crate) f3_v: F,
+}
+
+impl<F: Field> CompressedGKRProof<F> {
+    /// Recover the full proof from the claimed sum.
+    ///
+    /// The challenges are needed to recover the claim of each round, so `rng` should be in the
+    /// same state as the one passed to `GKRRoundSumcheck::verify`, and it is advanced in the same
+    /// way.
+    pub fn decompress<R: FeedableRNG>(
+        &self,
+        rng: &mut R,
+        claimed_sum: F,
+    ) -> Result<GKRProof<F>, crate::Error> {
+        self.decompress_with_subclaim(rng, claimed_sum).map(|r| r.0)
+    }
+
+    /// Recover the full proof, along with the subclaim that `GKRRoundSumcheck::verify` returns for
+    /// it.
+    pub(crate) fn decompress_with_subclaim<R: FeedableRNG>(
+        &self,
+        rng: &mut R,
+        claimed_sum: F,
+    ) -> Result<(GKRProof<F>, GKRRoundSumcheckSubClaim<F>), crate::Error> {
+        let (phase1_sumcheck_msgs, phase1_subclaim) =
+            decompress_phase(rng, &self.phase1_sumcheck_msgs, claimed_sum)?;
+        rng.feed(&self.f2_u).unwrap();
+        let (phase2_sumcheck_msgs, phase2_subclaim) = decompress_phase(
+            rng,
+            &self.phase2_sumcheck_msgs,
+            phase1_subclaim.expected_evaluation,
+        )?;
+        let proof = GKRProof {
+            phase1_sumcheck_msgs,
+            phase2_sumcheck_msgs,
+            f2_u: self.f2_u,
+            f3_v: self.f3_v,
+        };
+        let subclaim = GKRRoundSumcheckSubClaim {
+            u: phase1_subclaim.point,
+            v: phase2_subclaim.point,
+            expected_evaluation: phase2_subclaim.expected_evaluation,
+            x_factors_evaluation: self.f2_u,
+            y_factors_evaluation: self.f3_v,
+        };
+        Ok((proof, subclaim))
+    }
+}
+
+/// Recover the round messages of one sumcheck phase starting from `claim`, feeding them into `rng`
+/// as the verifier does. Returns the messages along with the subclaim of the phase.
+fn decompress_phase<F: Field, R: FeedableRNG>(
+    rng: &mut R,
+    msgs: &[ProverMsg<F>],
+    claim: F,
+) -> Result<(Vec<ProverMsg<F>>, SubClaim<F>), crate::Error> {
+    let mut expected = claim;
+    let mut point = Vec::with_capacity(msgs.len());
+    let mut full_msgs = Vec::with_capacity(msgs.len());
+    for (i, msg) in msgs.iter().enumerate() {
+        if msg.evaluations.is_empty() {
+            return Err(crate::Error::Reject(Some(ark_std::format!(
+                "Compressed prover message at round {} has no evaluations.",
+                i
+            ))));
+        }
+        let mut evaluations = Vec::with_capacity(msg.evaluations.len() + 1);
+        evaluations.push(expected - msg.evaluations[0]);
+        evaluations.extend_from_slice(&msg.evaluations);
+        let full_msg = ProverMsg { evaluations };
+        rng.feed(&full_msg).unwrap();
+        let r = IPForMLSumcheck::sample_round(rng).randomness;
+        expected = interpolate_uni_poly(&full_msg.evaluations, r);
+        point.push(r);
+        full_msgs.push(full_msg);
+    }
+    let subclaim = SubClaim {
+        point,
+        expected_evaluation: expected,
+    };
+    Ok((full_msgs, subclaim))
 }
 
 /// Subclaim for GKR Round Function
@@ -26,23 +161,50 @@ pub struct GKRRoundSumcheckSubClaim<F: Field> {
     pub v: Vec<F>,
     /// expected evaluation at f(g,u,v)
     pub expected_evaluation: F,
+    /// claimed evaluation of the dense factors on `x` at `u` (`f2(u)`, or the product of all
+    /// factors on `x` for a generalized function), as recorded in the proof
+    pub x_factors_evaluation: F,
+    /// claimed evaluation of the dense factors on `y` at `v` (`f3(v)`, or the product of all
+    /// factors on `y` for a generalized function), as recorded in the proof
+    pub y_factors_evaluation: F,
 }
 
 impl<F: Field> GKRRoundSumcheckSubClaim<F> {
     /// Verify that the subclaim is true by evaluating the GKR Round function.
+    ///
+    /// Returns an error if the dimensions of the polynomials or `g` do not match the subclaim.
     pub fn verify_subclaim(
         &self,
         f1: &SparseMultilinearExtension<F>,
         f2: &DenseMultilinearExtension<F>,
         f3: &DenseMultilinearExtension<F>,
         g: &[F],
-    ) -> bool {
+    ) -> Result<bool, crate::Error> {
+        self.verify_subclaim_general(f1, &[f2], &[f3], g)
+    }
+
+    /// Verify that the subclaim is true by evaluating the generalized GKR Round function
+    /// `f1(g,x,y) * \prod_i fx_i(x) * \prod_j fy_j(y)`.
+    ///
+    /// Returns an error if the dimensions of the polynomials or `g` do not match the subclaim.
+    pub fn verify_subclaim_general(
+        &self,
+        f1: &SparseMultilinearExtension<F>,
+        x_factors: &[&DenseMultilinearExtension<F>],
+        y_factors: &[&DenseMultilinearExtension<F>],
+        g: &[F],
+    ) -> Result<bool, crate::Error> {
         let dim = self.u.len();
-        assert_eq!(self.v.len(), dim);
-        assert_eq!(f1.num_vars, 3 * dim);
-        assert_eq!(f2.num_vars, dim);
-        assert_eq!(f3.num_vars, dim);
-        assert_eq!(g.len(), dim);
+        if self.v.len() != dim
+            || f1.num_vars != 3 * dim
+            || x_factors.iter().any(|f| f.num_vars != dim)
+            || y_factors.iter().any(|f| f.num_vars != dim)
+            || g.len() != dim
+        {
+            return Err(crate::Error::OtherError(
+                "dimensions do not match the subclaim".into(),
+            ));
+        }
 
         let guv: Vec<_> = g
             .iter()
@@ -50,8 +212,42 @@ impl<F: Field> GKRRoundSumcheckSubClaim<F> {
             .chain(self.v.iter())
             .copied()
             .collect();
-        let actual_evaluation = f1.evaluate(&guv) * f2.evaluate(&self.u) * f3.evaluate(&self.v);
+        let actual_evaluation = f1.evaluate(&guv)
+            * x_factors.iter().map(|f| f.evaluate(&self.u)).product::<F>()
+            * y_factors.iter().map(|f| f.evaluate(&self.v)).product::<F>();
+
+        Ok(actual_evaluation == self.expected_evaluation)
+    }
 
-        actual_evaluation == self.expected_evaluation
+    /// Verify that the subclaim is consistent with `f1`, using the claimed evaluations of the
+    /// dense factors carried by the subclaim instead of the factors themselves.
+    ///
+    /// This checks that `f1(g,u,v) * x_factors_evaluation * y_factors_evaluation` equals the
+    /// expected evaluation. The caller is left to check `x_factors_evaluation` and
+    /// `y_factors_evaluation` against the factors, e.g. as claims for the next layer.
+    ///
+    /// Returns an error if the dimensions of `f1` or `g` do not match the subclaim.
+    pub fn verify_subclaim_with_f1(
+        &self,
+        f1: &SparseMultilinearExtension<F>,
+        g: &[F],
+    ) -> Result<bool, crate::Error> {
+        let dim = self.u.len();
+        if self.v.len() != dim || f1.num_vars != 3 * dim || g.len() != dim {
+            return Err(crate::Error::OtherError(
+                "dimensions do not match the subclaim".into(),
+            ));
+        }
+
+        let guv: Vec<_> = g
+            .iter()
+            .chain(self.u.iter())
+            .chain(self.v.iter())
+            .copied()
+            .collect();
+        Ok(
+            f1.evaluate(&guv) * self.x_factors_evaluation * self.y_factors_evaluation
+                == self.expected_evaluation,
+        )
     }
 }
@@ -6,10 +6,14 @@ pub mod data_structures;
 #[cfg(test)]
 mod test;
 
-use crate::gkr_round_sumcheck::data_structures::{GKRProof, GKRRoundSumcheckSubClaim};
-use crate::ml_sumcheck::protocol::prover::ProverState;
+use crate::gkr_round_sumcheck::data_structures::{
+    CompressedGKRProof, GKRProof, GKRRoundSumcheckSubClaim,
+};
+use crate::ml_sumcheck::data_structures::precompute_eq;
+use crate::ml_sumcheck::protocol::prover::{ProverMsg, ProverState};
 use crate::ml_sumcheck::protocol::{IPForMLSumcheck, ListOfProductsOfPolynomials, PolynomialInfo};
-use crate::rng::FeedableRNG;
+use crate::ml_sumcheck::MLSumcheck;
+use crate::rng::{Blake2b512Rng, FeedableRNG};
 use ark_ff::{Field, Zero};
 use ark_poly::{
     DenseMultilinearExtension, MultilinearExtension, Polynomial, SparseMultilinearExtension,
@@ -41,6 +45,34 @@ pub fn initialize_phase_one<F: Field>(
     (hg, f1_at_g)
 }
 
+/// Takes multilinear f1, sparse f3, and input g = g1,...,gl. Returns h_g, and f1 fixed at g.
+///
+/// Same as `initialize_phase_one`, but only looks up the nonzero entries of `f3`, so that `f3`
+/// never needs to be materialized as a dense table.
+pub fn initialize_phase_one_sparse<F: Field>(
+    f1: &SparseMultilinearExtension<F>,
+    f3: &SparseMultilinearExtension<F>,
+    g: &[F],
+) -> (DenseMultilinearExtension<F>, SparseMultilinearExtension<F>) {
+    let dim = f3.num_vars; // 'l` in paper
+    assert_eq!(f1.num_vars, dim * 3);
+    assert_eq!(g.len(), dim);
+    let mut a_hg: Vec<_> = (0..(1 << dim)).map(|_| F::zero()).collect();
+    let f1_at_g = f1.fix_variables(g);
+    for (xy, v) in f1_at_g.evaluations.iter() {
+        if v != &F::zero() {
+            let x = xy & ((1 << dim) - 1);
+            let y = xy >> dim;
+            if let Some(f3_y) = f3.evaluations.get(&y) {
+                a_hg[x] += *v * f3_y;
+            }
+        }
+    }
+
+    let hg = DenseMultilinearExtension::from_evaluations_vec(dim, a_hg);
+    (hg, f1_at_g)
+}
+
 /// Takes h_g and returns a sumcheck state
 pub fn start_phase1_sumcheck<F: Field>(
     h_g: &DenseMultilinearExtension<F>,
@@ -59,7 +91,29 @@ pub fn initialize_phase_two<F: Field>(
     u: &[F],
 ) -> DenseMultilinearExtension<F> {
     assert_eq!(u.len() * 2, f1_g.num_vars);
-    f1_g.fix_variables(u).to_dense_multilinear_extension()
+    fix_variables_prefix(f1_g, u)
+}
+
+/// Takes f1 fixed at g, and u. Returns f1 fixed at g||u as a dense table.
+///
+/// Equivalent to `f1_g.fix_variables(u).to_dense_multilinear_extension()`, but only visits the
+/// nonzero entries of `f1_g` once, accumulating `f1_g[x||y] * eq(u, x)` into the entry for `y`.
+pub(crate) fn fix_variables_prefix<F: Field>(
+    f1_g: &SparseMultilinearExtension<F>,
+    u: &[F],
+) -> DenseMultilinearExtension<F> {
+    let dim = u.len();
+    assert_eq!(f1_g.num_vars, dim * 2);
+    let eq_u = precompute_eq(u);
+    let mut a_f1: Vec<_> = (0..(1 << dim)).map(|_| F::zero()).collect();
+    for (xy, v) in f1_g.evaluations.iter() {
+        if v != &F::zero() {
+            let x = xy & ((1 << dim) - 1);
+            let y = xy >> dim;
+            a_f1[y] += eq_u[x] * v;
+        }
+    }
+    DenseMultilinearExtension::from_evaluations_vec(dim, a_f1)
 }
 
 /// Takes f1 fixed at g||u, f3, and f2 evaluated at u.
@@ -81,6 +135,49 @@ pub fn start_phase2_sumcheck<F: Field>(
     IPForMLSumcheck::prover_init(&poly)
 }
 
+/// Takes f1 fixed at g||u, sparse f3, and f2 evaluated at u.
+///
+/// The sumcheck prover works on dense tables, so `f3` scaled by `f2_u` is written into a dense
+/// table here, by only visiting the nonzero entries of `f3`.
+pub fn start_phase2_sumcheck_sparse<F: Field>(
+    f1_gu: &DenseMultilinearExtension<F>,
+    f3: &SparseMultilinearExtension<F>,
+    f2_u: F,
+) -> ProverState<F> {
+    let dim = f1_gu.num_vars;
+    assert_eq!(f3.num_vars, dim);
+    let mut a_f3_f2u = vec![F::zero(); 1 << dim];
+    for (y, v) in f3.evaluations.iter() {
+        a_f3_f2u[*y] = f2_u * v;
+    }
+    let f3_f2u = DenseMultilinearExtension::from_evaluations_vec(dim, a_f3_f2u);
+
+    let mut poly = ListOfProductsOfPolynomials::new(dim);
+    poly.add_product(vec![Rc::new(f1_gu.clone()), Rc::new(f3_f2u)], F::one());
+    IPForMLSumcheck::prover_init(&poly)
+}
+
+/// Runs all rounds of a sumcheck, feeding each prover message into `rng` before sampling the
+/// verifier message. Returns the prover messages and the sampled randomness.
+fn run_sumcheck_rounds<F: Field, R: FeedableRNG>(
+    rng: &mut R,
+    prover_state: &mut ProverState<F>,
+    dim: usize,
+) -> (Vec<ProverMsg<F>>, Vec<F>) {
+    let mut verifier_msg = None;
+    let mut prover_msgs = Vec::with_capacity(dim);
+    let mut randomness = Vec::with_capacity(dim);
+    for _ in 0..dim {
+        let pm = IPForMLSumcheck::prove_round(prover_state, &verifier_msg);
+        rng.feed(&pm).unwrap();
+        prover_msgs.push(pm);
+        let vm = IPForMLSumcheck::sample_round(rng);
+        randomness.push(vm.randomness);
+        verifier_msg = Some(vm);
+    }
+    (prover_msgs, randomness)
+}
+
 /// Sumcheck Argument for GKR Round Function
 pub struct GKRRoundSumcheck<F: Field> {
     _marker: PhantomData<F>,
@@ -100,48 +197,211 @@ impl<F: Field> GKRRoundSumcheck<F> {
         assert_eq!(f1.num_vars, 3 * f2.num_vars);
         assert_eq!(f1.num_vars, 3 * f3.num_vars);
 
-        let dim = f2.num_vars;
-        let g = g.to_vec();
+        Self::prove_general(rng, f1, &[f2], &[f3], g)
+    }
+
+    /// Same as `prove`, but also returns the points `u` and `v` sampled in phase one and phase
+    /// two. The proof is identical to the one returned by `prove`.
+    pub fn prove_with_points<R: FeedableRNG>(
+        rng: &mut R,
+        f1: &SparseMultilinearExtension<F>,
+        f2: &DenseMultilinearExtension<F>,
+        f3: &DenseMultilinearExtension<F>,
+        g: &[F],
+    ) -> (GKRProof<F>, Vec<F>, Vec<F>) {
+        assert_eq!(f1.num_vars, 3 * f2.num_vars);
+        assert_eq!(f1.num_vars, 3 * f3.num_vars);
+
+        let (h_g, f1_g) = initialize_phase_one(f1, f3, g);
+        Self::prove_from_phase_one(rng, h_g, &f1_g, &[f2], &[f3])
+    }
+
+    /// Takes a generalized GKR Round Function `f1(g,x,y) * \prod_i fx_i(x) * \prod_j fy_j(y)` and
+    /// input, prove the sum.
+    /// * `f1`: the sparse wiring predicate with `3 * g.len()` variables
+    /// * `x_factors`: the dense factors `fx_i` on `x`
+    /// * `y_factors`: the dense factors `fy_j` on `y`
+    /// * `g`: represents the fixed input.
+    ///
+    /// `prove` is the special case with `x_factors = [f2]` and `y_factors = [f3]`.
+    pub fn prove_general<R: FeedableRNG>(
+        rng: &mut R,
+        f1: &SparseMultilinearExtension<F>,
+        x_factors: &[&DenseMultilinearExtension<F>],
+        y_factors: &[&DenseMultilinearExtension<F>],
+        g: &[F],
+    ) -> GKRProof<F> {
+        let dim = g.len();
+        assert_eq!(f1.num_vars, 3 * dim);
+        assert!(x_factors.iter().all(|f| f.num_vars == dim));
+        assert!(y_factors.iter().all(|f| f.num_vars == dim));
+
+        let y_product;
+        let f3 = if y_factors.len() == 1 {
+            y_factors[0]
+        } else {
+            y_product = pointwise_product(dim, y_factors);
+            &y_product
+        };
+        let (h_g, f1_g) = initialize_phase_one(f1, f3, g);
+        Self::prove_from_phase_one(rng, h_g, &f1_g, x_factors, y_factors).0
+    }
+
+    /// Runs both phases of the generalized GKR Round sumcheck, given h_g and f1 fixed at g as
+    /// returned by `initialize_phase_one`. Returns the proof, `u` and `v`.
+    fn prove_from_phase_one<R: FeedableRNG>(
+        rng: &mut R,
+        h_g: DenseMultilinearExtension<F>,
+        f1_g: &SparseMultilinearExtension<F>,
+        x_factors: &[&DenseMultilinearExtension<F>],
+        y_factors: &[&DenseMultilinearExtension<F>],
+    ) -> (GKRProof<F>, Vec<F>, Vec<F>) {
+        let dim = h_g.num_vars;
+        let mut phase1_ps = {
+            let mut poly = ListOfProductsOfPolynomials::new(dim);
+            poly.add_product(
+                ark_std::iter::once(Rc::new(h_g))
+                    .chain(x_factors.iter().map(|&f| Rc::new(f.clone()))),
+                F::one(),
+            );
+            IPForMLSumcheck::prover_init(&poly)
+        };
+        let (phase1_prover_msgs, u) = run_sumcheck_rounds(rng, &mut phase1_ps, dim);
 
-        let (h_g, f1_g) = initialize_phase_one(f1, f3, &g);
+        let f1_gu = initialize_phase_two(f1_g, &u);
+        let x_factors_at_u = x_factors.iter().map(|f| f.evaluate(&u)).product();
+        rng.feed(&x_factors_at_u).unwrap();
+        let mut phase2_ps = if y_factors.len() == 1 {
+            start_phase2_sumcheck(&f1_gu, y_factors[0], x_factors_at_u)
+        } else {
+            let mut poly = ListOfProductsOfPolynomials::new(dim);
+            poly.add_product(
+                ark_std::iter::once(Rc::new(f1_gu))
+                    .chain(y_factors.iter().map(|&f| Rc::new(f.clone()))),
+                x_factors_at_u,
+            );
+            IPForMLSumcheck::prover_init(&poly)
+        };
+        let (phase2_prover_msgs, v) = run_sumcheck_rounds(rng, &mut phase2_ps, dim);
+        let y_factors_at_v = y_factors.iter().map(|f| f.evaluate(&v)).product();
+
+        let proof = GKRProof {
+            phase1_sumcheck_msgs: phase1_prover_msgs,
+            phase2_sumcheck_msgs: phase2_prover_msgs,
+            f2_u: x_factors_at_u,
+            f3_v: y_factors_at_v,
+        };
+        (proof, u, v)
+    }
+
+    /// Takes a GKR Round Function and input, prove the sum non-interactively.
+    ///
+    /// Unlike `prove`, the Fiat-Shamir transcript is set up internally, and the number of
+    /// variables of `f1` and `f3`, the input `g` and the sum are fed into it before any round,
+    /// so the prover and the verifier cannot disagree on the public inputs. Use `verify_fs` to
+    /// verify the proof.
+    pub fn prove_fs(
+        f1: &SparseMultilinearExtension<F>,
+        f2: &DenseMultilinearExtension<F>,
+        f3: &DenseMultilinearExtension<F>,
+        g: &[F],
+    ) -> Result<GKRProof<F>, crate::Error> {
+        assert_eq!(f1.num_vars, 3 * f2.num_vars);
+        assert_eq!(f1.num_vars, 3 * f3.num_vars);
+
+        let (h_g, f1_g) = initialize_phase_one(f1, f3, g);
+        let sum = h_g
+            .evaluations
+            .iter()
+            .zip(f2.evaluations.iter())
+            .map(|(h, f)| *h * f)
+            .sum();
+        let mut rng = Self::setup_fs_rng(f1.num_vars, f3.num_vars, g, sum)?;
+        Ok(Self::prove_from_phase_one(&mut rng, h_g, &f1_g, &[f2], &[f3]).0)
+    }
+
+    /// Takes a proof generated by `prove_fs`, input, and the claimed sum, and returns a subclaim.
+    /// * `f2_num_vars`: represents number of variables of f2
+    pub fn verify_fs(
+        f2_num_vars: usize,
+        g: &[F],
+        proof: &GKRProof<F>,
+        claimed_sum: F,
+    ) -> Result<GKRRoundSumcheckSubClaim<F>, crate::Error> {
+        let mut rng = Self::setup_fs_rng(3 * f2_num_vars, f2_num_vars, g, claimed_sum)?;
+        Self::verify(&mut rng, f2_num_vars, proof, claimed_sum)
+    }
+
+    /// Sets up the transcript used by `prove_fs` and `verify_fs`.
+    fn setup_fs_rng(
+        f1_num_vars: usize,
+        f3_num_vars: usize,
+        g: &[F],
+        sum: F,
+    ) -> Result<Blake2b512Rng, crate::Error> {
+        let mut rng = Blake2b512Rng::setup();
+        rng.feed(&f1_num_vars)?;
+        rng.feed(&f3_num_vars)?;
+        rng.feed(&g.to_vec())?;
+        rng.feed(&sum)?;
+        Ok(rng)
+    }
+
+    /// Takes a GKR Round Function whose `f3` is sparse and input, prove the sum.
+    ///
+    /// The proof is the same as the one produced by `prove` with `f3` densified, but `f3` is only
+    /// read through its nonzero entries.
+    pub fn prove_with_sparse_f3<R: FeedableRNG>(
+        rng: &mut R,
+        f1: &SparseMultilinearExtension<F>,
+        f2: &DenseMultilinearExtension<F>,
+        f3: &SparseMultilinearExtension<F>,
+        g: &[F],
+    ) -> GKRProof<F> {
+        assert_eq!(f1.num_vars, 3 * f2.num_vars);
+        assert_eq!(f1.num_vars, 3 * f3.num_vars);
+
+        let dim = f2.num_vars;
+        let (h_g, f1_g) = initialize_phase_one_sparse(f1, f3, g);
         let mut phase1_ps = start_phase1_sumcheck(&h_g, f2);
-        let mut phase1_vm = None;
-        let mut phase1_prover_msgs = Vec::with_capacity(dim);
-        let mut u = Vec::with_capacity(dim);
-        for _ in 0..dim {
-            let pm = IPForMLSumcheck::prove_round(&mut phase1_ps, &phase1_vm);
-
-            rng.feed(&pm).unwrap();
-            phase1_prover_msgs.push(pm);
-            let vm = IPForMLSumcheck::sample_round(rng);
-            phase1_vm = Some(vm.clone());
-            u.push(vm.randomness);
-        }
+        let (phase1_prover_msgs, u) = run_sumcheck_rounds(rng, &mut phase1_ps, dim);
 
         let f1_gu = initialize_phase_two(&f1_g, &u);
-        let mut phase2_ps = start_phase2_sumcheck(&f1_gu, f3, f2.evaluate(&u));
-        let mut phase2_vm = None;
-        let mut phase2_prover_msgs = Vec::with_capacity(dim);
-        let mut v = Vec::with_capacity(dim);
-        for _ in 0..dim {
-            let pm = IPForMLSumcheck::prove_round(&mut phase2_ps, &phase2_vm);
-            rng.feed(&pm).unwrap();
-            phase2_prover_msgs.push(pm);
-            let vm = IPForMLSumcheck::sample_round(rng);
-            phase2_vm = Some(vm.clone());
-            v.push(vm.randomness);
-        }
+        let f2_u = f2.evaluate(&u);
+        rng.feed(&f2_u).unwrap();
+        let mut phase2_ps = start_phase2_sumcheck_sparse(&f1_gu, f3, f2_u);
+        let (phase2_prover_msgs, v) = run_sumcheck_rounds(rng, &mut phase2_ps, dim);
 
         GKRProof {
             phase1_sumcheck_msgs: phase1_prover_msgs,
             phase2_sumcheck_msgs: phase2_prover_msgs,
+            f2_u,
+            f3_v: f3.evaluate(&v),
         }
     }
 
+    /// Returns an upper bound on the size in bytes of the compressed serialization of a proof
+    /// generated by `prove_general` with `num_x_factors` and `num_y_factors` dense factors of `dim`
+    /// variables, without generating or serializing the proof.
+    ///
+    /// This is not the exact size: a round only has the maximal degree `1 + num_x_factors` (resp.
+    /// `1 + num_y_factors`) if every factor and `f1` at `g` depend on its variable, and the
+    /// prover sends one evaluation less per missing degree. The bound is reached for factors in
+    /// general position. The exact size of a proof is given by its `compressed_size`.
+    pub fn max_proof_size_bytes(dim: usize, num_x_factors: usize, num_y_factors: usize) -> usize {
+        let phase_size = |num_factors: usize| {
+            MLSumcheck::<F>::max_proof_size_bytes(&PolynomialInfo {
+                max_multiplicands: 1 + num_factors,
+                num_variables: dim,
+            })
+        };
+        phase_size(num_x_factors) + phase_size(num_y_factors) + 2 * F::zero().compressed_size()
+    }
+
     /// Takes a GKR Round Function, input, and proof, and returns a subclaim.
     ///
-    /// If the `claimed_sum` is correct, then it is `subclaim.verify_subclaim` will return true.
-    /// Otherwise, it is very likely that `subclaim.verify_subclaim` will return false.
+    /// If the `claimed_sum` is correct, then it is `subclaim.verify_subclaim` will return `Ok(true)`.
+    /// Otherwise, it is very likely that `subclaim.verify_subclaim` will return `Ok(false)`.
     /// Larger field size guarantees smaller soundness error.
     /// * `f2_num_vars`: represents number of variables of f2
     pub fn verify<R: FeedableRNG>(
@@ -150,28 +410,106 @@ impl<F: Field> GKRRoundSumcheck<F> {
         proof: &GKRProof<F>,
         claimed_sum: F,
     ) -> Result<GKRRoundSumcheckSubClaim<F>, crate::Error> {
-        // verify first sumcheck
-        let dim = f2_num_vars;
+        Self::verify_general(rng, f2_num_vars, 1, 1, proof, claimed_sum)
+    }
+
+    /// Same as `verify`, but takes a proof compressed by `GKRProof::compress`.
+    ///
+    /// The omitted evaluations are recovered from the running claim, so the consistency check of
+    /// each round holds by construction, and the subclaim is the same as the one `verify` returns
+    /// for the full proof.
+    pub fn verify_compressed<R: FeedableRNG>(
+        rng: &mut R,
+        f2_num_vars: usize,
+        proof: &CompressedGKRProof<F>,
+        claimed_sum: F,
+    ) -> Result<GKRRoundSumcheckSubClaim<F>, crate::Error> {
+        if proof.phase1_sumcheck_msgs.len() != f2_num_vars
+            || proof.phase2_sumcheck_msgs.len() != f2_num_vars
+        {
+            return Err(crate::Error::Reject(Some("Proof is incomplete.".into())));
+        }
+        // each round polynomial has degree at most 2, so at most 2 evaluations remain
+        if proof
+            .phase1_sumcheck_msgs
+            .iter()
+            .chain(proof.phase2_sumcheck_msgs.iter())
+            .any(|msg| msg.evaluations.len() > 2)
+        {
+            return Err(crate::Error::Reject(Some(
+                "Prover message has too many evaluations.".into(),
+            )));
+        }
+        proof
+            .decompress_with_subclaim(rng, claimed_sum)
+            .map(|r| r.1)
+    }
 
+    /// Takes `f1`, input, and proof, and returns a subclaim, without needing f2 or f3.
+    ///
+    /// In addition to `verify`, this checks that `f1(g,u,v) * f2(u) * f3(v)` equals the
+    /// expected evaluation, where `f2(u)` and `f3(v)` are the claimed evaluations recorded in the
+    /// proof. The caller is then left to check `proof.f2_u()` and `proof.f3_v()`, e.g. as claims
+    /// for the next layer.
+    pub fn verify_with_f1<R: FeedableRNG>(
+        rng: &mut R,
+        f1: &SparseMultilinearExtension<F>,
+        g: &[F],
+        proof: &GKRProof<F>,
+        claimed_sum: F,
+    ) -> Result<GKRRoundSumcheckSubClaim<F>, crate::Error> {
+        let dim = g.len();
+        assert_eq!(f1.num_vars, 3 * dim);
+        let subclaim = Self::verify(rng, dim, proof, claimed_sum)?;
+
+        if !subclaim.verify_subclaim_with_f1(f1, g)? {
+            return Err(crate::Error::Reject(Some(
+                "Claimed evaluations of f2 and f3 are not consistent with the subclaim.".into(),
+            )));
+        }
+        Ok(subclaim)
+    }
+
+    /// Takes the shape of a generalized GKR Round Function, and proof, and returns a subclaim.
+    ///
+    /// If the `claimed_sum` is correct, then `subclaim.verify_subclaim_general` will return `Ok(true)`.
+    /// * `dim`: represents number of variables of each dense factor
+    /// * `num_x_factors`, `num_y_factors`: number of dense factors on `x` and on `y`
+    pub fn verify_general<R: FeedableRNG>(
+        rng: &mut R,
+        dim: usize,
+        num_x_factors: usize,
+        num_y_factors: usize,
+        proof: &GKRProof<F>,
+        claimed_sum: F,
+    ) -> Result<GKRRoundSumcheckSubClaim<F>, crate::Error> {
+        if proof.phase1_sumcheck_msgs.len() != dim || proof.phase2_sumcheck_msgs.len() != dim {
+            return Err(crate::Error::Reject(Some(ark_std::format!(
+                "Proof has {} and {} round messages, expected {} in each phase.",
+                proof.phase1_sumcheck_msgs.len(),
+                proof.phase2_sumcheck_msgs.len(),
+                dim
+            ))));
+        }
+        // verify first sumcheck
         let mut phase1_vs = IPForMLSumcheck::verifier_init(&PolynomialInfo {
-            max_multiplicands: 2,
+            max_multiplicands: 1 + num_x_factors,
             num_variables: dim,
         });
 
-        for i in 0..dim {
-            let pm = &proof.phase1_sumcheck_msgs[i];
+        for pm in &proof.phase1_sumcheck_msgs {
             rng.feed(pm).unwrap();
             let _result = IPForMLSumcheck::verify_round((*pm).clone(), &mut phase1_vs, rng);
         }
         let phase1_subclaim = IPForMLSumcheck::check_and_generate_subclaim(phase1_vs, claimed_sum)?;
         let u = phase1_subclaim.point;
+        rng.feed(&proof.f2_u).unwrap();
 
         let mut phase2_vs = IPForMLSumcheck::verifier_init(&PolynomialInfo {
-            max_multiplicands: 2,
+            max_multiplicands: 1 + num_y_factors,
             num_variables: dim,
         });
-        for i in 0..dim {
-            let pm = &proof.phase2_sumcheck_msgs[i];
+        for pm in &proof.phase2_sumcheck_msgs {
             rng.feed(pm).unwrap();
             let _result = IPForMLSumcheck::verify_round((*pm).clone(), &mut phase2_vs, rng);
         }
@@ -188,6 +526,23 @@ impl<F: Field> GKRRoundSumcheck<F> {
             u,
             v,
             expected_evaluation,
+            x_factors_evaluation: proof.f2_u,
+            y_factors_evaluation: proof.f3_v,
         })
     }
 }
+
+/// Takes a list of multilinear extensions with `dim` variables and returns the extension of the
+/// pointwise product of their evaluations over the boolean hypercube.
+fn pointwise_product<F: Field>(
+    dim: usize,
+    factors: &[&DenseMultilinearExtension<F>],
+) -> DenseMultilinearExtension<F> {
+    let mut evaluations = vec![F::one(); 1 << dim];
+    for factor in factors {
+        for (e, f) in evaluations.iter_mut().zip(factor.evaluations.iter()) {
+            *e *= f;
+        }
+    }
+    DenseMultilinearExtension::from_evaluations_vec(dim, evaluations)
+}
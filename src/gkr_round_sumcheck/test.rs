@@ -1,7 +1,10 @@
-use crate::gkr_round_sumcheck::GKRRoundSumcheck;
+use crate::gkr_round_sumcheck::{fix_variables_prefix, CompressedGKRProof, GKRRoundSumcheck};
 use crate::rng::{Blake2b512Rng, FeedableRNG};
 use ark_ff::Field;
-use ark_poly::{DenseMultilinearExtension, MultilinearExtension, SparseMultilinearExtension};
+use ark_poly::{
+    DenseMultilinearExtension, MultilinearExtension, Polynomial, SparseMultilinearExtension,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::rand::RngCore;
 use ark_std::{test_rng, UniformRand};
 use ark_test_curves::bls12_381::Fr;
@@ -64,7 +67,7 @@ fn test_circuit<F: Field>(nv: usize) {
     rng = Blake2b512Rng::setup();
     let subclaim = GKRRoundSumcheck::verify(&mut rng, f2.num_vars, &proof, claimed_sum)
         .expect("verification failed");
-    let result = subclaim.verify_subclaim(&f1, &f2, &f3, &g);
+    let result = subclaim.verify_subclaim(&f1, &f2, &f3, &g).unwrap();
     assert!(result)
 }
 
@@ -82,7 +85,289 @@ fn test_extract() {
     let expected_sum = calculate_sum_naive(&f1, &f2, &f3, &g);
     let mut rng = Blake2b512Rng::setup();
     let proof = GKRRoundSumcheck::prove(&mut rng, &f1, &f2, &f3, &g);
-    let actual_sum = proof.extract_sum();
+    let actual_sum = proof.extract_sum().unwrap();
 
     assert_eq!(actual_sum, expected_sum);
 }
+
+#[test]
+fn test_truncated_proof() {
+    let nv = 6;
+    let mut rng = test_rng();
+    let (f1, f2, f3) = random_gkr_instance::<Fr, _>(nv, &mut rng);
+    let g: Vec<_> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+    let mut fs_rng = Blake2b512Rng::setup();
+    let proof = GKRRoundSumcheck::prove(&mut fs_rng, &f1, &f2, &f3, &g);
+    let claimed_sum = proof.extract_sum().unwrap();
+
+    let mut truncated = proof.clone();
+    truncated.phase1_sumcheck_msgs.pop();
+    let mut fs_rng = Blake2b512Rng::setup();
+    assert!(matches!(
+        GKRRoundSumcheck::verify(&mut fs_rng, nv, &truncated, claimed_sum),
+        Err(crate::Error::Reject(_))
+    ));
+
+    let mut truncated = proof.clone();
+    truncated.phase2_sumcheck_msgs.truncate(1);
+    let mut fs_rng = Blake2b512Rng::setup();
+    assert!(matches!(
+        GKRRoundSumcheck::verify(&mut fs_rng, nv, &truncated, claimed_sum),
+        Err(crate::Error::Reject(_))
+    ));
+
+    let mut truncated = proof;
+    truncated.phase1_sumcheck_msgs.clear();
+    assert!(matches!(
+        truncated.extract_sum(),
+        Err(crate::Error::Reject(_))
+    ));
+}
+
+#[test]
+fn test_general() {
+    let nv = 6;
+    let mut rng = test_rng();
+    let (f1, f2, f3) = random_gkr_instance::<Fr, _>(nv, &mut rng);
+    let f4 = DenseMultilinearExtension::rand(nv, &mut rng);
+    let g: Vec<_> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+    let f2_f4 = DenseMultilinearExtension::from_evaluations_vec(
+        nv,
+        f2.evaluations
+            .iter()
+            .zip(f4.evaluations.iter())
+            .map(|(a, b)| *a * b)
+            .collect(),
+    );
+    let claimed_sum = calculate_sum_naive(&f1, &f2_f4, &f3, &g);
+
+    let mut rng = Blake2b512Rng::setup();
+    let proof = GKRRoundSumcheck::prove_general(&mut rng, &f1, &[&f2, &f4], &[&f3], &g);
+    assert_eq!(proof.extract_sum().unwrap(), claimed_sum);
+    rng = Blake2b512Rng::setup();
+    let subclaim = GKRRoundSumcheck::verify_general(&mut rng, nv, 2, 1, &proof, claimed_sum)
+        .expect("verification failed");
+    assert!(subclaim
+        .verify_subclaim_general(&f1, &[&f2, &f4], &[&f3], &g)
+        .unwrap());
+    assert!(!subclaim.verify_subclaim(&f1, &f2, &f3, &g).unwrap());
+    assert_eq!(
+        subclaim.x_factors_evaluation,
+        f2.evaluate(&subclaim.u) * f4.evaluate(&subclaim.u)
+    );
+    assert_eq!(subclaim.y_factors_evaluation, f3.evaluate(&subclaim.v));
+    assert!(subclaim.verify_subclaim_with_f1(&f1, &g).unwrap());
+}
+
+#[test]
+fn test_sparse_f3() {
+    let nv = 7;
+    let mut rng = test_rng();
+    let (f1, f2, _) = random_gkr_instance::<Fr, _>(nv, &mut rng);
+    let f3_sparse = SparseMultilinearExtension::rand_with_config(nv, nv, &mut rng);
+    let f3 = f3_sparse.to_dense_multilinear_extension();
+    let g: Vec<_> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+    let claimed_sum = calculate_sum_naive(&f1, &f2, &f3, &g);
+
+    let mut rng = Blake2b512Rng::setup();
+    let sparse_proof = GKRRoundSumcheck::prove_with_sparse_f3(&mut rng, &f1, &f2, &f3_sparse, &g);
+    rng = Blake2b512Rng::setup();
+    let sparse_subclaim = GKRRoundSumcheck::verify(&mut rng, nv, &sparse_proof, claimed_sum)
+        .expect("verification failed");
+    assert!(sparse_subclaim.verify_subclaim(&f1, &f2, &f3, &g).unwrap());
+
+    rng = Blake2b512Rng::setup();
+    let dense_proof = GKRRoundSumcheck::prove(&mut rng, &f1, &f2, &f3, &g);
+    rng = Blake2b512Rng::setup();
+    let dense_subclaim = GKRRoundSumcheck::verify(&mut rng, nv, &dense_proof, claimed_sum)
+        .expect("verification failed");
+    assert_eq!(sparse_subclaim.u, dense_subclaim.u);
+    assert_eq!(sparse_subclaim.v, dense_subclaim.v);
+    assert_eq!(
+        sparse_subclaim.expected_evaluation,
+        dense_subclaim.expected_evaluation
+    );
+}
+
+#[test]
+fn test_verify_with_f1() {
+    let nv = 6;
+    let mut rng = test_rng();
+    let (f1, f2, f3) = random_gkr_instance::<Fr, _>(nv, &mut rng);
+    let g: Vec<_> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+    let claimed_sum = calculate_sum_naive(&f1, &f2, &f3, &g);
+    let mut rng = Blake2b512Rng::setup();
+    let proof = GKRRoundSumcheck::prove(&mut rng, &f1, &f2, &f3, &g);
+
+    // the verifier only holds the proof and f1
+    rng = Blake2b512Rng::setup();
+    let subclaim = GKRRoundSumcheck::verify_with_f1(&mut rng, &f1, &g, &proof, claimed_sum)
+        .expect("verification failed");
+    assert_eq!(proof.f2_u(), f2.evaluate(&subclaim.u));
+    assert_eq!(proof.f3_v(), f3.evaluate(&subclaim.v));
+    assert!(subclaim.verify_subclaim(&f1, &f2, &f3, &g).unwrap());
+
+    let mut wrong_proof = proof;
+    wrong_proof.f3_v += Fr::from(1u64);
+    rng = Blake2b512Rng::setup();
+    assert!(
+        GKRRoundSumcheck::verify_with_f1(&mut rng, &f1, &g, &wrong_proof, claimed_sum).is_err()
+    );
+}
+
+#[test]
+fn test_fs() {
+    let nv = 6;
+    let mut rng = test_rng();
+    let (f1, f2, f3) = random_gkr_instance::<Fr, _>(nv, &mut rng);
+    let g: Vec<_> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+    let claimed_sum = calculate_sum_naive(&f1, &f2, &f3, &g);
+
+    let proof = GKRRoundSumcheck::prove_fs(&f1, &f2, &f3, &g).expect("fail to prove");
+    let another_proof = GKRRoundSumcheck::prove_fs(&f1, &f2, &f3, &g).expect("fail to prove");
+    let mut bytes = Vec::new();
+    proof.serialize_compressed(&mut bytes).unwrap();
+    let mut another_bytes = Vec::new();
+    another_proof
+        .serialize_compressed(&mut another_bytes)
+        .unwrap();
+    assert_eq!(bytes, another_bytes);
+
+    let subclaim =
+        GKRRoundSumcheck::verify_fs(nv, &g, &proof, claimed_sum).expect("verification failed");
+    assert!(subclaim.verify_subclaim(&f1, &f2, &f3, &g).unwrap());
+
+    let mut another_g = g.clone();
+    another_g[0] += Fr::from(1u64);
+    let result = GKRRoundSumcheck::verify_fs(nv, &another_g, &proof, claimed_sum);
+    assert!(result.map_or(true, |s| !s.verify_subclaim(&f1, &f2, &f3, &g).unwrap()));
+}
+
+#[test]
+fn test_fix_variables_prefix() {
+    let dim = 14;
+    let mut rng = test_rng();
+    let (f1, _, _) = random_gkr_instance::<Fr, _>(dim, &mut rng);
+    let g: Vec<_> = (0..dim).map(|_| Fr::rand(&mut rng)).collect();
+    let u: Vec<_> = (0..dim).map(|_| Fr::rand(&mut rng)).collect();
+    let f1_g = f1.fix_variables(&g);
+    assert_eq!(
+        fix_variables_prefix(&f1_g, &u),
+        f1_g.fix_variables(&u).to_dense_multilinear_extension()
+    );
+}
+
+#[test]
+fn test_prove_with_points() {
+    let nv = 5;
+    let mut rng = test_rng();
+    let (f1, f2, f3) = random_gkr_instance::<Fr, _>(nv, &mut rng);
+    let g: Vec<_> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+    let claimed_sum = calculate_sum_naive(&f1, &f2, &f3, &g);
+
+    let mut rng = Blake2b512Rng::setup();
+    let (proof, u, v) = GKRRoundSumcheck::prove_with_points(&mut rng, &f1, &f2, &f3, &g);
+    let mut rng = Blake2b512Rng::setup();
+    let another_proof = GKRRoundSumcheck::prove(&mut rng, &f1, &f2, &f3, &g);
+    assert_eq!(proof, another_proof);
+
+    let mut rng = Blake2b512Rng::setup();
+    let subclaim = GKRRoundSumcheck::verify(&mut rng, f2.num_vars, &proof, claimed_sum)
+        .expect("verification failed");
+    assert_eq!(subclaim.u, u);
+    assert_eq!(subclaim.v, v);
+}
+
+#[test]
+fn test_verify_subclaim_dimension_mismatch() {
+    let nv = 4;
+    let mut rng = test_rng();
+    let (f1, f2, f3) = random_gkr_instance::<Fr, _>(nv, &mut rng);
+    let g: Vec<_> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+    let claimed_sum = calculate_sum_naive(&f1, &f2, &f3, &g);
+    let mut fs_rng = Blake2b512Rng::setup();
+    let proof = GKRRoundSumcheck::prove(&mut fs_rng, &f1, &f2, &f3, &g);
+    let mut fs_rng = Blake2b512Rng::setup();
+    let subclaim = GKRRoundSumcheck::verify(&mut fs_rng, nv, &proof, claimed_sum)
+        .expect("verification failed");
+    assert!(subclaim.verify_subclaim(&f1, &f2, &f3, &g).unwrap());
+
+    let another_f2 = DenseMultilinearExtension::rand(nv, &mut rng);
+    assert!(!subclaim.verify_subclaim(&f1, &another_f2, &f3, &g).unwrap());
+
+    let (small_f1, small_f2, small_f3) = random_gkr_instance::<Fr, _>(nv - 1, &mut rng);
+    assert!(subclaim
+        .verify_subclaim(&small_f1, &small_f2, &small_f3, &g[1..])
+        .is_err());
+    assert!(subclaim.verify_subclaim(&f1, &small_f2, &f3, &g).is_err());
+    assert!(subclaim.verify_subclaim(&f1, &f2, &f3, &g[1..]).is_err());
+}
+
+#[test]
+fn test_max_proof_size_bytes() {
+    let mut rng = test_rng();
+    for dim in 1..6 {
+        let (f1, f2, f3) = random_gkr_instance::<Fr, _>(dim, &mut rng);
+        let f4 = DenseMultilinearExtension::rand(dim, &mut rng);
+        let g: Vec<_> = (0..dim).map(|_| Fr::rand(&mut rng)).collect();
+        let mut fs_rng = Blake2b512Rng::setup();
+        let proof = GKRRoundSumcheck::prove(&mut fs_rng, &f1, &f2, &f3, &g);
+        assert_eq!(
+            GKRRoundSumcheck::<Fr>::max_proof_size_bytes(dim, 1, 1),
+            proof.compressed_size()
+        );
+        let mut fs_rng = Blake2b512Rng::setup();
+        let proof = GKRRoundSumcheck::prove_general(&mut fs_rng, &f1, &[&f2, &f4], &[&f3], &g);
+        assert_eq!(
+            GKRRoundSumcheck::<Fr>::max_proof_size_bytes(dim, 2, 1),
+            proof.compressed_size()
+        );
+
+        // a constant f2 lowers the degree of phase one
+        let constant_f2 = DenseMultilinearExtension::from_evaluations_vec(
+            dim,
+            vec![Fr::rand(&mut rng); 1 << dim],
+        );
+        let mut fs_rng = Blake2b512Rng::setup();
+        let proof = GKRRoundSumcheck::prove(&mut fs_rng, &f1, &constant_f2, &f3, &g);
+        assert!(proof.compressed_size() < GKRRoundSumcheck::<Fr>::max_proof_size_bytes(dim, 1, 1));
+    }
+}
+
+#[test]
+fn test_compressed_proof() {
+    let nv = 6;
+    let mut rng = test_rng();
+    let (f1, f2, f3) = random_gkr_instance::<Fr, _>(nv, &mut rng);
+    let g: Vec<_> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+    let claimed_sum = calculate_sum_naive(&f1, &f2, &f3, &g);
+    let mut fs_rng = Blake2b512Rng::setup();
+    let proof = GKRRoundSumcheck::prove(&mut fs_rng, &f1, &f2, &f3, &g);
+    let compressed = proof.compress();
+    assert!(compressed.compressed_size() < proof.compressed_size());
+
+    let mut fs_rng = Blake2b512Rng::setup();
+    let decompressed = compressed.decompress(&mut fs_rng, claimed_sum).unwrap();
+    assert_eq!(decompressed, proof);
+
+    let mut bytes = Vec::new();
+    compressed.serialize_compressed(&mut bytes).unwrap();
+    let deserialized = CompressedGKRProof::deserialize_compressed(&bytes[..]).unwrap();
+    assert_eq!(deserialized, compressed);
+
+    let mut fs_rng = Blake2b512Rng::setup();
+    let expected = GKRRoundSumcheck::verify(&mut fs_rng, nv, &proof, claimed_sum).unwrap();
+    let mut fs_rng = Blake2b512Rng::setup();
+    let subclaim =
+        GKRRoundSumcheck::verify_compressed(&mut fs_rng, nv, &compressed, claimed_sum).unwrap();
+    assert_eq!(subclaim.u, expected.u);
+    assert_eq!(subclaim.v, expected.v);
+    assert_eq!(subclaim.expected_evaluation, expected.expected_evaluation);
+    assert!(subclaim.verify_subclaim(&f1, &f2, &f3, &g).unwrap());
+
+    let mut fs_rng = Blake2b512Rng::setup();
+    let wrong_sum = claimed_sum + Fr::from(1u64);
+    let subclaim =
+        GKRRoundSumcheck::verify_compressed(&mut fs_rng, nv, &compressed, wrong_sum).unwrap();
+    assert!(!subclaim.verify_subclaim(&f1, &f2, &f3, &g).unwrap());
+}
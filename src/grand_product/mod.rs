@@ -0,0 +1,201 @@
+//! Grand product argument: proves that the product of all entries of a multilinear extension
+//! equals a claimed value.
+//!
+//! The prover builds a layered product circuit, where layer `0` is the input table and each
+//! entry of layer `j + 1` is the product of the two entries of layer `j` that differ only in the
+//! last variable. The last layer has a single entry, the grand product. Working from the output
+//! layer down, a claim `V_{j+1}(r) = c` is reduced to a claim about `V_j` by running `ml_sumcheck`
+//! on
+//!
+//! $$c = \sum_{x} eq(r, x) \cdot V_j(x, 0) \cdot V_j(x, 1)$$
+//!
+//! The layers have different numbers of variables, so each reduction uses `MLSumcheck` on a
+//! product of three multilinear extensions rather than `GKRRoundSumcheck`, whose wiring
+//! function assumes all layers have the same size.
+
+#[cfg(test)]
+mod test;
+
+use crate::ml_sumcheck::data_structures::{eq_mle, ListOfProductsOfPolynomials};
+use crate::ml_sumcheck::protocol::PolynomialInfo;
+use crate::ml_sumcheck::{MLSumcheck, Proof};
+use crate::rng::FeedableRNG;
+use ark_ff::Field;
+use ark_poly::{DenseMultilinearExtension, Polynomial};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::marker::PhantomData;
+use ark_std::rc::Rc;
+use ark_std::vec::Vec;
+
+/// Grand product argument for the entries of a multilinear extension
+pub struct GrandProduct<F: Field>(#[doc(hidden)] PhantomData<F>);
+
+/// Proof of one layer of the product circuit
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct GrandProductLayerProof<F: Field> {
+    /// sumcheck proof reducing the claim about the layer above to this layer (empty for the
+    /// layer right below the output)
+    pub sumcheck_proof: Proof<F>,
+    /// evaluations of this layer at `(r, 0)` and `(r, 1)`, where `r` is the sumcheck point
+    pub evaluations: (F, F),
+}
+
+/// Proof generated by `GrandProduct::prove`
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct GrandProductProof<F: Field> {
+    /// proofs of the layers, from the output layer down to the input layer
+    pub layer_proofs: Vec<GrandProductLayerProof<F>>,
+}
+
+/// Subclaim returned by `GrandProduct::verify`, which is a claim about the input polynomial
+pub struct GrandProductSubClaim<F: Field> {
+    /// the multi-dimensional point that the input multilinear extension is evaluated to
+    pub point: Vec<F>,
+    /// the expected evaluation
+    pub expected_evaluation: F,
+}
+
+impl<F: Field> GrandProductSubClaim<F> {
+    /// Verify that the subclaim is true by evaluating the input polynomial.
+    pub fn verify_subclaim(&self, evals: &DenseMultilinearExtension<F>) -> bool {
+        evals.num_vars == self.point.len()
+            && evals.evaluate(&self.point) == self.expected_evaluation
+    }
+}
+
+impl<F: Field> GrandProduct<F> {
+    /// Takes the evaluations of a multilinear extension, and prove the product of all of them.
+    /// Returns the product along with the proof.
+    pub fn prove(
+        evals: &DenseMultilinearExtension<F>,
+        rng: &mut impl FeedableRNG<Error = crate::Error>,
+    ) -> Result<(F, GrandProductProof<F>), crate::Error> {
+        let mut layers = vec![evals.clone()];
+        for _ in 0..evals.num_vars {
+            let layer = layers.last().unwrap();
+            let (low, high) = split_last_variable(layer);
+            let next: Vec<F> = low
+                .evaluations
+                .iter()
+                .zip(high.evaluations.iter())
+                .map(|(a, b)| *a * b)
+                .collect();
+            layers.push(DenseMultilinearExtension::from_evaluations_vec(
+                layer.num_vars - 1,
+                next,
+            ));
+        }
+        let product = layers.last().unwrap().evaluations[0];
+
+        rng.feed(&evals.num_vars)?;
+        rng.feed(&product)?;
+        let mut point = Vec::new();
+        let mut layer_proofs = Vec::with_capacity(evals.num_vars);
+        for layer in layers.iter().rev().skip(1) {
+            let (low, high) = split_last_variable(layer);
+            let sumcheck_proof = if point.is_empty() {
+                Vec::new()
+            } else {
+                let eq = eq_mle(&point);
+                let mut poly = ListOfProductsOfPolynomials::new(point.len());
+                poly.add_product(
+                    vec![Rc::new(eq), Rc::new(low.clone()), Rc::new(high.clone())],
+                    F::one(),
+                );
+                let (proof, prover_state) = MLSumcheck::prove_as_subprotocol(rng, &poly)?;
+                point = prover_state.randomness;
+                proof
+            };
+            let evaluations = (low.evaluate(&point), high.evaluate(&point));
+            rng.feed(&evaluations.0)?;
+            rng.feed(&evaluations.1)?;
+            point.push(F::rand(rng));
+            layer_proofs.push(GrandProductLayerProof {
+                sumcheck_proof,
+                evaluations,
+            });
+        }
+
+        Ok((product, GrandProductProof { layer_proofs }))
+    }
+
+    /// Takes the number of variables of the input, the claimed product and the proof, and returns
+    /// a subclaim about the input polynomial.
+    pub fn verify(
+        num_vars: usize,
+        claimed_product: F,
+        proof: &GrandProductProof<F>,
+        rng: &mut impl FeedableRNG<Error = crate::Error>,
+    ) -> Result<GrandProductSubClaim<F>, crate::Error> {
+        if proof.layer_proofs.len() != num_vars {
+            return Err(crate::Error::Reject(Some(
+                "Number of layers does not match the number of variables.".into(),
+            )));
+        }
+        rng.feed(&num_vars)?;
+        rng.feed(&claimed_product)?;
+        let mut point = Vec::new();
+        let mut expected_evaluation = claimed_product;
+        for layer_proof in proof.layer_proofs.iter() {
+            let eq_eval = if point.is_empty() {
+                F::one()
+            } else {
+                if layer_proof.sumcheck_proof.len() != point.len() {
+                    return Err(crate::Error::Reject(Some(
+                        "Sumcheck proof of a layer is incomplete.".into(),
+                    )));
+                }
+                let info = PolynomialInfo {
+                    max_multiplicands: 3,
+                    num_variables: point.len(),
+                };
+                let subclaim = MLSumcheck::verify_as_subprotocol(
+                    rng,
+                    &info,
+                    expected_evaluation,
+                    &layer_proof.sumcheck_proof,
+                )?;
+                expected_evaluation = subclaim.expected_evaluation;
+                let eq_eval = eq_eval(&point, &subclaim.point);
+                point = subclaim.point;
+                eq_eval
+            };
+            let (low, high) = layer_proof.evaluations;
+            if eq_eval * low * high != expected_evaluation {
+                return Err(crate::Error::Reject(Some(
+                    "Layer evaluations do not match the sumcheck subclaim.".into(),
+                )));
+            }
+            rng.feed(&low)?;
+            rng.feed(&high)?;
+            let r = F::rand(rng);
+            expected_evaluation = low + r * (high - low);
+            point.push(r);
+        }
+
+        Ok(GrandProductSubClaim {
+            point,
+            expected_evaluation,
+        })
+    }
+}
+
+/// Splits `poly` into the two halves where the last variable is fixed to 0 and 1.
+fn split_last_variable<F: Field>(
+    poly: &DenseMultilinearExtension<F>,
+) -> (DenseMultilinearExtension<F>, DenseMultilinearExtension<F>) {
+    let nv = poly.num_vars - 1;
+    let (low, high) = poly.evaluations.split_at(1 << nv);
+    (
+        DenseMultilinearExtension::from_evaluations_slice(nv, low),
+        DenseMultilinearExtension::from_evaluations_slice(nv, high),
+    )
+}
+
+/// Evaluates eq(a, b) for two points of the same dimension.
+fn eq_eval<F: Field>(a: &[F], b: &[F]) -> F {
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| *a * b + (F::one() - a) * (F::one() - b))
+        .product()
+}
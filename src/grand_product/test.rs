@@ -0,0 +1,53 @@
+use crate::grand_product::GrandProduct;
+use crate::rng::{Blake2b512Rng, FeedableRNG};
+use ark_ff::Field;
+use ark_poly::{DenseMultilinearExtension, MultilinearExtension};
+use ark_std::test_rng;
+use ark_test_curves::bls12_381::Fr;
+
+#[test]
+fn test_grand_product() {
+    let nv = 8;
+    let mut rng = test_rng();
+    let evals = DenseMultilinearExtension::<Fr>::rand(nv, &mut rng);
+    let expected: Fr = evals.evaluations.iter().product();
+
+    let mut fs_rng = Blake2b512Rng::setup();
+    let (product, proof) = GrandProduct::prove(&evals, &mut fs_rng).expect("fail to prove");
+    assert_eq!(product, expected);
+
+    let mut fs_rng = Blake2b512Rng::setup();
+    let subclaim =
+        GrandProduct::verify(nv, product, &proof, &mut fs_rng).expect("verification failed");
+    assert!(subclaim.verify_subclaim(&evals));
+}
+
+#[test]
+fn test_tampered_product() {
+    let nv = 8;
+    let mut rng = test_rng();
+    let evals = DenseMultilinearExtension::<Fr>::rand(nv, &mut rng);
+
+    let mut fs_rng = Blake2b512Rng::setup();
+    let (product, proof) = GrandProduct::prove(&evals, &mut fs_rng).expect("fail to prove");
+
+    let mut fs_rng = Blake2b512Rng::setup();
+    let result = GrandProduct::verify(nv, product + Fr::ONE, &proof, &mut fs_rng);
+    assert!(result.map_or(true, |subclaim| !subclaim.verify_subclaim(&evals)));
+}
+
+#[test]
+fn test_single_element() {
+    let mut rng = test_rng();
+    let evals = DenseMultilinearExtension::<Fr>::rand(0, &mut rng);
+
+    let mut fs_rng = Blake2b512Rng::setup();
+    let (product, proof) = GrandProduct::prove(&evals, &mut fs_rng).expect("fail to prove");
+    assert_eq!(product, evals.evaluations[0]);
+    assert!(proof.layer_proofs.is_empty());
+
+    let mut fs_rng = Blake2b512Rng::setup();
+    let subclaim =
+        GrandProduct::verify(0, product, &proof, &mut fs_rng).expect("verification failed");
+    assert!(subclaim.verify_subclaim(&evals));
+}
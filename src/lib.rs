@@ -7,7 +7,7 @@
 #![deny(unused_attributes, unused_mut)]
 #![deny(missing_docs)]
 #![deny(unused_imports)]
-#![deny(renamed_and_removed_lints, stable_features, unused_allocation)]
+#![deny(renamed_and_removed_lints, unused_allocation)]
 #![deny(unused_comparisons, bare_trait_objects, unused_must_use)]
 
 pub use error::Error;
@@ -20,7 +20,9 @@ extern crate ark_std;
 mod error;
 
 pub mod gkr_round_sumcheck;
+pub mod grand_product;
 pub mod ml_sumcheck;
+pub mod permutation_check;
 
 pub mod rng;
 
@@ -1,9 +1,12 @@
 //! Defines the data structures used by the `MLSumcheck` protocol.
 
 use ark_ff::Field;
-use ark_poly::{DenseMultilinearExtension, Polynomial};
+use ark_poly::{
+    DenseMultilinearExtension, MultilinearExtension, Polynomial, SparseMultilinearExtension,
+};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::cmp::max;
+use ark_std::collections::BTreeMap;
 use ark_std::rc::Rc;
 use ark_std::vec::Vec;
 use hashbrown::HashMap;
@@ -54,6 +57,50 @@ pub struct PolynomialInfo {
     pub num_variables: usize,
 }
 
+impl PolynomialInfo {
+    /// Max degree of the round polynomials sent by the prover, which is `max_multiplicands`. A
+    /// polynomial without products is the zero polynomial, whose round polynomials are sent with
+    /// degree one.
+    pub fn degree_bound(&self) -> usize {
+        max(self.max_multiplicands, 1)
+    }
+
+    /// Estimate the work of the sumcheck verifier for this polynomial, assuming every round
+    /// polynomial has degree `degree_bound()`. This does not include evaluating the polynomial
+    /// at the point of the subclaim.
+    ///
+    /// Each interpolation of `d + 1` evaluations at a point `r` computes `\prod_j (r - j)` with
+    /// `d` multiplications, and then takes 4 multiplications and 1 inversion per evaluation to
+    /// add its term. The barycentric weight of each evaluation is computed as a fraction, which
+    /// takes 1 multiplication per evaluation for `d < 33`. For larger degrees, the fractions are
+    /// computed in the field, which takes `d` multiplications for `d!` and 2 per step between
+    /// consecutive evaluations.
+    pub fn verifier_cost(&self) -> VerifierCost {
+        let degree = self.degree_bound();
+        let fraction_multiplications = if degree < 33 { degree + 1 } else { 3 * degree };
+        VerifierCost {
+            interpolations: self.num_variables,
+            interpolation_degree: degree,
+            field_multiplications: self.num_variables
+                * (degree + 4 * (degree + 1) + fraction_multiplications),
+            field_inversions: self.num_variables * (degree + 1),
+        }
+    }
+}
+
+/// Estimated work of the sumcheck verifier, as returned by `PolynomialInfo::verifier_cost`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerifierCost {
+    /// number of round polynomials the verifier interpolates, one for each variable
+    pub interpolations: usize,
+    /// degree of each interpolated round polynomial
+    pub interpolation_degree: usize,
+    /// number of field multiplications
+    pub field_multiplications: usize,
+    /// number of field inversions
+    pub field_inversions: usize,
+}
+
 impl<F: Field> ListOfProductsOfPolynomials<F> {
     /// Returns an empty polynomial
     pub fn new(num_variables: usize) -> Self {
@@ -95,6 +142,98 @@ impl<F: Field> ListOfProductsOfPolynomials<F> {
         self.products.push((coefficient, indexed_product));
     }
 
+    /// Add all products of `other`, with their coefficients multiplied by `scalar`.
+    ///
+    /// Multiplicands of `other` that are already stored in `self` (i.e. the same `Rc`) are reused
+    /// instead of being stored twice.
+    pub fn merge(&mut self, other: &Self, scalar: F) {
+        assert_eq!(
+            other.num_variables, self.num_variables,
+            "merged polynomial has wrong number of variables"
+        );
+        for (coefficient, product) in &other.products {
+            self.add_product(
+                product
+                    .iter()
+                    .map(|&i| other.flattened_ml_extensions[i].clone()),
+                scalar * coefficient,
+            );
+        }
+    }
+
+    /// Returns the polynomial in `num_variables - 1` variables obtained by fixing the first
+    /// variable to `r`.
+    ///
+    /// Each unique multiplicand is fixed once, and products that shared a multiplicand still
+    /// share the fixed one.
+    pub fn fix_first_variable(&self, r: F) -> Self {
+        assert!(self.num_variables > 0, "no variable left to fix");
+        let fixed: Vec<Rc<DenseMultilinearExtension<F>>> = self
+            .flattened_ml_extensions
+            .iter()
+            .map(|m| Rc::new(m.fix_variables(&[r])))
+            .collect();
+        let mut result = Self::new(self.num_variables - 1);
+        for (coefficient, product) in &self.products {
+            result.add_product(product.iter().map(|&i| fixed[i].clone()), *coefficient);
+        }
+        result
+    }
+
+    /// Returns the number of multiplicands of each product, in the order of `self.products`.
+    pub fn product_degrees(&self) -> Vec<usize> {
+        self.products.iter().map(|(_, p)| p.len()).collect()
+    }
+
+    /// Estimate the max number of multiplicands that depend on variable `round`, over all
+    /// products with a nonzero coefficient and no all-zero multiplicand, after the first
+    /// `fixed_so_far.len()` variables are fixed to `fixed_so_far`.
+    ///
+    /// This is the degree of the round polynomial at round `round`. Variables before `round` that
+    /// are not fixed are left free, so that the result is an upper bound over all their values.
+    pub fn max_multiplicands_at_round(&self, round: usize, fixed_so_far: &[F]) -> usize {
+        assert!(round < self.num_variables, "round out of range");
+        assert!(
+            fixed_so_far.len() <= round,
+            "variable of the round is fixed"
+        );
+        let var = round - fixed_so_far.len();
+        let is_zero: Vec<bool> = self
+            .flattened_ml_extensions
+            .iter()
+            .map(|m| is_zero_table(&m.evaluations))
+            .collect();
+        let depends: Vec<bool> = self
+            .flattened_ml_extensions
+            .iter()
+            .map(|m| {
+                if fixed_so_far.is_empty() {
+                    depends_on_variable(&m.evaluations, 2, 1 << var)
+                } else {
+                    depends_on_variable(&m.fix_variables(fixed_so_far).evaluations, 2, 1 << var)
+                }
+            })
+            .collect();
+        max_product_degree(&nonzero_products(&self.products, &is_zero), &depends).unwrap_or(0)
+    }
+
+    /// Compute the sum of the polynomial over the boolean hypercube {0,1}^`num_variables`
+    pub fn sum_over_hypercube(&self) -> F {
+        (0..1 << self.num_variables)
+            .map(|b| {
+                self.products
+                    .iter()
+                    .map(|(c, p)| {
+                        *c * p
+                            .iter()
+                            .map(|&i| self.flattened_ml_extensions[i][b])
+                            .product::<F>()
+                    })
+                    .sum::<F>()
+            })
+            .sum()
+    }
+
     /// Evaluate the polynomial at point `point`
     pub fn evaluate(&self, point: &[F]) -> F {
         self.products
@@ -108,3 +247,283 @@ impl<F: Field> ListOfProductsOfPolynomials<F> {
             .sum()
     }
 }
+
+/// An oracle that evaluates a polynomial at a given point.
+///
+/// The verifier only needs the polynomial to check the final subclaim, so in recursive settings the
+/// polynomial can be given by an oracle (e.g. the subclaim of a previous proof) instead of an
+/// explicit table. Any closure `Fn(&[F]) -> F` is an oracle.
+pub trait EvaluationOracle<F: Field> {
+    /// Evaluate the polynomial at `point`.
+    fn evaluate(&self, point: &[F]) -> F;
+}
+
+impl<F: Field, T: Fn(&[F]) -> F> EvaluationOracle<F> for T {
+    fn evaluate(&self, point: &[F]) -> F {
+        self(point)
+    }
+}
+
+impl<F: Field> EvaluationOracle<F> for ListOfProductsOfPolynomials<F> {
+    fn evaluate(&self, point: &[F]) -> F {
+        ListOfProductsOfPolynomials::evaluate(self, point)
+    }
+}
+
+/// Fix the last `point.len()` variables of `poly`. Given `P(x_1, ..., x_n)` and
+/// `point = (r_{k+1}, ..., r_n)`, returns `P'(x_1, ..., x_k) = P(x_1, ..., x_k, r_{k+1}, ..., r_n)`.
+///
+/// This is the counterpart of `MultilinearExtension::fix_variables`, which fixes the first
+/// variables. Each step folds the high half of the table into the low half.
+///
+/// Returns an error if `point` has more coordinates than `poly` has variables.
+pub fn fix_last_variables<F: Field>(
+    poly: &DenseMultilinearExtension<F>,
+    point: &[F],
+) -> Result<DenseMultilinearExtension<F>, crate::Error> {
+    if point.len() > poly.num_vars {
+        return Err(crate::Error::OtherError(
+            "point has more coordinates than the polynomial has variables".into(),
+        ));
+    }
+    let mut table = poly.evaluations.clone();
+    for r in point.iter().rev() {
+        let half = table.len() / 2;
+        let (low, high) = table.split_at_mut(half);
+        for (l, h) in low.iter_mut().zip(high.iter()) {
+            *l += *r * (*h - *l);
+        }
+        table.truncate(half);
+    }
+    Ok(DenseMultilinearExtension::from_evaluations_vec(
+        poly.num_vars - point.len(),
+        table,
+    ))
+}
+
+/// Returns the multilinear extension of the pointwise product of the evaluation tables of `a`
+/// and `b`.
+///
+/// The product of two multilinear polynomials is not multilinear, so the result only agrees with
+/// `a * b` on the boolean hypercube. This is useful for working with evaluation tables, not for
+/// evaluating the product outside of the hypercube.
+///
+/// Returns an error if `a` and `b` have different numbers of variables.
+pub fn hadamard<F: Field>(
+    a: &DenseMultilinearExtension<F>,
+    b: &DenseMultilinearExtension<F>,
+) -> Result<DenseMultilinearExtension<F>, crate::Error> {
+    pointwise(a, b, |x, y| x * y)
+}
+
+/// Returns the pointwise sum of `a` and `b`, which is the multilinear extension `a + b`.
+///
+/// Returns an error if `a` and `b` have different numbers of variables.
+pub fn add_poly<F: Field>(
+    a: &DenseMultilinearExtension<F>,
+    b: &DenseMultilinearExtension<F>,
+) -> Result<DenseMultilinearExtension<F>, crate::Error> {
+    pointwise(a, b, |x, y| x + y)
+}
+
+fn pointwise<F: Field>(
+    a: &DenseMultilinearExtension<F>,
+    b: &DenseMultilinearExtension<F>,
+    op: impl Fn(F, F) -> F,
+) -> Result<DenseMultilinearExtension<F>, crate::Error> {
+    if a.num_vars != b.num_vars {
+        return Err(crate::Error::OtherError(
+            "polynomials have different numbers of variables".into(),
+        ));
+    }
+    Ok(DenseMultilinearExtension::from_evaluations_vec(
+        a.num_vars,
+        a.evaluations
+            .iter()
+            .zip(b.evaluations.iter())
+            .map(|(x, y)| op(*x, *y))
+            .collect(),
+    ))
+}
+
+/// Returns the sparse multilinear extension with the evaluations given by `pairs` of
+/// `(index, value)`, where the values of duplicate indices are added together instead of being
+/// rejected. Indices whose values sum to zero are dropped, so the result has one entry per
+/// distinct index with a nonzero accumulated value.
+///
+/// Returns an error if an index is out of range for `num_vars` variables.
+pub fn from_slice_accumulating<F: Field>(
+    num_vars: usize,
+    pairs: &[(usize, F)],
+) -> Result<SparseMultilinearExtension<F>, crate::Error> {
+    let mut accumulated = BTreeMap::new();
+    for &(index, value) in pairs {
+        if index >> num_vars != 0 {
+            return Err(crate::Error::OtherError("index out of range".into()));
+        }
+        *accumulated.entry(index).or_insert_with(F::zero) += value;
+    }
+    let evaluations: Vec<(usize, F)> = accumulated
+        .into_iter()
+        .filter(|(_, value)| !value.is_zero())
+        .collect();
+    Ok(SparseMultilinearExtension::from_evaluations(
+        num_vars,
+        &evaluations,
+    ))
+}
+
+/// Evaluate `poly` at each of `points`.
+///
+/// The evaluation table is copied into a single scratch buffer that is folded in place for each
+/// point, instead of allocating a new table per point. The results are the same as
+/// `poly.evaluate(point)`.
+///
+/// Returns an error if a point does not have `poly.num_vars` coordinates.
+pub fn eval_at_batch<F: Field>(
+    poly: &DenseMultilinearExtension<F>,
+    points: &[&[F]],
+) -> Result<Vec<F>, crate::Error> {
+    if points.iter().any(|point| point.len() != poly.num_vars) {
+        return Err(crate::Error::OtherError(
+            "point does not match the number of variables".into(),
+        ));
+    }
+    let mut scratch = poly.evaluations.clone();
+    Ok(points
+        .iter()
+        .map(|point| {
+            scratch.copy_from_slice(&poly.evaluations);
+            let mut len = scratch.len();
+            for r in point.iter() {
+                len /= 2;
+                for b in 0..len {
+                    let left = scratch[b << 1];
+                    let right = scratch[(b << 1) + 1];
+                    scratch[b] = left + *r * (right - left);
+                }
+            }
+            scratch[0]
+        })
+        .collect())
+}
+
+/// Returns the multilinear extension of eq(t, x) = \prod_i (t_i x_i + (1 - t_i)(1 - x_i)) as a
+/// function of x.
+pub fn eq_mle<F: Field>(t: &[F]) -> DenseMultilinearExtension<F> {
+    DenseMultilinearExtension::from_evaluations_vec(t.len(), precompute_eq(t))
+}
+
+/// Returns the factors of eq(t, x), one for each variable. The ith factor is the
+/// `t.len()`-variate multilinear extension of 1 - t_i - x_i + 2 t_i x_i, and the product of all
+/// factors is `eq_mle(t)`.
+pub fn eq_extension<F: Field>(t: &[F]) -> Vec<DenseMultilinearExtension<F>> {
+    let nv = t.len();
+    t.iter()
+        .enumerate()
+        .map(|(i, t_i)| {
+            let evaluations = (0..(1 << nv))
+                .map(|x| {
+                    if (x >> i) & 1 == 1 {
+                        *t_i
+                    } else {
+                        F::one() - t_i
+                    }
+                })
+                .collect();
+            DenseMultilinearExtension::from_evaluations_vec(nv, evaluations)
+        })
+        .collect()
+}
+
+/// Returns the table of eq(r, x) for all x in {0,1}^`r.len()`, where the first variable is the
+/// least significant bit of the index.
+pub(crate) fn precompute_eq<F: Field>(r: &[F]) -> Vec<F> {
+    let mut table = Vec::with_capacity(1 << r.len());
+    table.push(F::one());
+    for r_i in r {
+        let len = table.len();
+        for x in 0..len {
+            let v = table[x];
+            table.push(v * r_i);
+            table[x] = v - table[x + len];
+        }
+    }
+    table
+}
+
+/// Returns whether every evaluation in `table` is zero.
+pub(crate) fn is_zero_table<F: Field>(table: &[F]) -> bool {
+    table.iter().all(|e| e.is_zero())
+}
+
+/// Returns whether `table` depends on the variable whose index has weight `stride`, where each
+/// variable ranges over `k` values.
+///
+/// The evaluations that only differ in that variable are `stride` apart, so the table depends on
+/// it if and only if one of those groups of `k` evaluations is not constant. Returns at the first
+/// such group.
+pub(crate) fn depends_on_variable<F: Field>(table: &[F], k: usize, stride: usize) -> bool {
+    table.chunks(k * stride).any(|block| {
+        (0..stride).any(|offset| (1..k).any(|j| block[offset + j * stride] != block[offset]))
+    })
+}
+
+/// Returns the products with a nonzero coefficient and no multiplicand whose table is all zero,
+/// given whether each table `is_zero`.
+///
+/// The other products are zero everywhere, so dropping them does not change the sum or any of the
+/// round polynomials.
+pub(crate) fn nonzero_products<F: Field>(
+    products: &[(F, Vec<usize>)],
+    is_zero: &[bool],
+) -> Vec<(F, Vec<usize>)> {
+    products
+        .iter()
+        .filter(|(coefficient, product)| {
+            !coefficient.is_zero() && product.iter().all(|&i| !is_zero[i])
+        })
+        .cloned()
+        .collect()
+}
+
+/// Returns the max number of multiplicands that depend on a variable over all `products`, given
+/// whether each table `depends` on it, or `None` if there is no product.
+///
+/// A multiplicand that does not depend on the variable is constant in it, so this is the degree
+/// of the products in that variable when each multiplicand has degree one.
+pub(crate) fn max_product_degree<F: Field>(
+    products: &[(F, Vec<usize>)],
+    depends: &[bool],
+) -> Option<usize> {
+    products
+        .iter()
+        .map(|(_, product)| product.iter().filter(|&&i| depends[i]).count())
+        .max()
+}
+
+/// Returns the dense multilinear extension with `num_vars` variables whose evaluations are given
+/// by `pairs` of `(index, value)`, treating every index that is not listed as zero.
+///
+/// Returns an error if an index is out of range or listed more than once.
+pub fn from_sparse_pairs<F: Field>(
+    num_vars: usize,
+    pairs: &[(usize, F)],
+) -> Result<DenseMultilinearExtension<F>, crate::Error> {
+    let mut evaluations = vec![F::zero(); 1 << num_vars];
+    let mut filled = vec![false; 1 << num_vars];
+    for &(index, value) in pairs {
+        if index >> num_vars != 0 {
+            return Err(crate::Error::OtherError("index out of range".into()));
+        }
+        if filled[index] {
+            return Err(crate::Error::OtherError("duplicate index".into()));
+        }
+        filled[index] = true;
+        evaluations[index] = value;
+    }
+    Ok(DenseMultilinearExtension::from_evaluations_vec(
+        num_vars,
+        evaluations,
+    ))
+}
@@ -0,0 +1,357 @@
+//! Sumcheck over `H^n` for a general domain `H` of field elements, instead of the boolean
+//! hypercube.
+//!
+//! Each multiplicand is given by its evaluations over `H^n`, and is extended to the unique
+//! polynomial of degree less than `|H|` in each variable. When `H = {0, 1}`, this is exactly the
+//! multilinear sumcheck.
+
+use crate::ml_sumcheck::data_structures::{
+    depends_on_variable, is_zero_table, max_product_degree, nonzero_products,
+    ListOfProductsOfPolynomials, PolynomialInfo,
+};
+use crate::ml_sumcheck::protocol::prover::ProverMsg;
+use crate::ml_sumcheck::protocol::verifier::{interpolate_uni_poly, SubClaim};
+use crate::ml_sumcheck::protocol::IPForMLSumcheck;
+use crate::ml_sumcheck::Proof;
+use crate::rng::{Blake2b512Rng, FeedableRNG};
+use ark_ff::Field;
+use ark_std::cmp::max;
+use ark_std::marker::PhantomData;
+use ark_std::vec::Vec;
+
+/// Sumcheck over `domain^num_variables` for products of polynomials
+pub struct DomainSumcheck<F: Field>(#[doc(hidden)] PhantomData<F>);
+
+/// Stores a list of products of polynomials given by their evaluations over
+/// `domain^num_variables`, that is meant to be added together.
+///
+/// The evaluation at `(h_{i_1}, ..., h_{i_n})` is stored at index `i_1 + k * i_2 + ... +
+/// k^{n-1} * i_n`, where `k = domain.len()`, so the first variable is the least significant.
+#[derive(Clone)]
+pub struct DomainPolynomial<F: Field> {
+    /// the domain each variable is summed over
+    pub domain: Vec<F>,
+    /// max number of multiplicands in each product
+    pub max_multiplicands: usize,
+    /// number of variables of the polynomial
+    pub num_variables: usize,
+    /// list of products, where each multiplicand is an index into `tables`
+    pub products: Vec<(F, Vec<usize>)>,
+    /// evaluation tables of the multiplicands
+    pub tables: Vec<Vec<F>>,
+}
+
+impl<F: Field> DomainPolynomial<F> {
+    /// Returns an empty polynomial
+    pub fn new(domain: Vec<F>, num_variables: usize) -> Self {
+        DomainPolynomial {
+            domain,
+            max_multiplicands: 0,
+            num_variables,
+            products: Vec::new(),
+            tables: Vec::new(),
+        }
+    }
+
+    /// Add a list of evaluation tables that is meant to be multiplied together.
+    /// The resulting polynomial will be multiplied by the scalar `coefficient`.
+    ///
+    /// Returns an error if the product is empty, if a table does not have one evaluation for each
+    /// point of `domain^num_variables`, or if that number overflows `usize`.
+    pub fn add_product(
+        &mut self,
+        product: impl IntoIterator<Item = Vec<F>>,
+        coefficient: F,
+    ) -> Result<(), crate::Error> {
+        let size = self.size()?;
+        let product: Vec<Vec<F>> = product.into_iter().collect();
+        if product.is_empty() {
+            return Err(crate::Error::OtherError("product is empty".into()));
+        }
+        if product.iter().any(|table| table.len() != size) {
+            return Err(crate::Error::OtherError(
+                "table size mismatches domain".into(),
+            ));
+        }
+        let indexed_product = (self.tables.len()..self.tables.len() + product.len()).collect();
+        self.max_multiplicands = max(self.max_multiplicands, product.len());
+        self.tables.extend(product);
+        self.products.push((coefficient, indexed_product));
+        Ok(())
+    }
+
+    /// Returns the number of points of `domain^num_variables`, or an error if it overflows
+    /// `usize`.
+    fn size(&self) -> Result<usize, crate::Error> {
+        self.domain
+            .len()
+            .checked_pow(self.num_variables as u32)
+            .ok_or_else(|| crate::Error::OtherError("domain size overflows".into()))
+    }
+
+    /// Extract the max number of multiplicands and number of variables of the list of products.
+    pub fn info(&self) -> PolynomialInfo {
+        PolynomialInfo {
+            max_multiplicands: self.max_multiplicands,
+            num_variables: self.num_variables,
+        }
+    }
+
+    /// Compute the sum of the polynomial over `domain^num_variables`
+    pub fn sum_over_domain(&self) -> F {
+        // every table has one evaluation for each point of the domain
+        let size = self.tables.first().map_or(0, |table| table.len());
+        (0..size)
+            .map(|b| {
+                self.products
+                    .iter()
+                    .map(|(c, p)| *c * p.iter().map(|&i| self.tables[i][b]).product::<F>())
+                    .sum::<F>()
+            })
+            .sum()
+    }
+
+    /// Evaluate the polynomial at point `point`
+    pub fn evaluate(&self, point: &[F]) -> F {
+        assert_eq!(point.len(), self.num_variables);
+        let weights: Vec<Vec<F>> = point
+            .iter()
+            .map(|r| lagrange_weights(&self.domain, *r))
+            .collect();
+        let evaluations: Vec<F> = self
+            .tables
+            .iter()
+            .map(|table| {
+                let mut table = table.clone();
+                for w in &weights {
+                    table = fold(&table, w);
+                }
+                table[0]
+            })
+            .collect();
+        self.products
+            .iter()
+            .map(|(c, p)| *c * p.iter().map(|&i| evaluations[i]).product::<F>())
+            .sum()
+    }
+}
+
+impl<F: Field> From<&ListOfProductsOfPolynomials<F>> for DomainPolynomial<F> {
+    /// Convert a multilinear polynomial to a polynomial over the boolean domain `{0, 1}`.
+    fn from(polynomial: &ListOfProductsOfPolynomials<F>) -> Self {
+        DomainPolynomial {
+            domain: vec![F::zero(), F::one()],
+            max_multiplicands: polynomial.max_multiplicands,
+            num_variables: polynomial.num_variables,
+            products: polynomial.products.clone(),
+            tables: polynomial
+                .flattened_ml_extensions
+                .iter()
+                .map(|m| m.evaluations.clone())
+                .collect(),
+        }
+    }
+}
+
+impl<F: Field> DomainSumcheck<F> {
+    /// generate proof of the sum of polynomial over `domain^num_vars`
+    pub fn prove(polynomial: &DomainPolynomial<F>) -> Result<Proof<F>, crate::Error> {
+        let mut fs_rng = Blake2b512Rng::setup();
+        fs_rng.feed(&polynomial.domain)?;
+        Self::prove_as_subprotocol(&mut fs_rng, polynomial).map(|r| r.0)
+    }
+
+    /// This function does the same thing as `prove`, but it uses a `FeedableRNG` as the
+    /// transcript, and additionally returns the verifier challenges. The domain is not fed into
+    /// `fs_rng`.
+    ///
+    /// For the domain `{0, 1}`, the proof is the same as the one generated by
+    /// `MLSumcheck::prove_as_subprotocol` for the same polynomial.
+    pub fn prove_as_subprotocol(
+        fs_rng: &mut impl FeedableRNG<Error = crate::Error>,
+        polynomial: &DomainPolynomial<F>,
+    ) -> Result<(Proof<F>, Vec<F>), crate::Error> {
+        check_domain(&polynomial.domain)?;
+        if polynomial.num_variables == 0 {
+            return Err(crate::Error::OtherError(
+                "Attempt to prove a constant.".into(),
+            ));
+        }
+        fs_rng.feed(&polynomial.info())?;
+
+        let k = polynomial.domain.len();
+        let degree_bound = degree_bound(k, &polynomial.info());
+        let weights: Vec<Vec<F>> = (0..=degree_bound)
+            .map(|t| lagrange_weights(&polynomial.domain, F::from(t as u64)))
+            .collect();
+        // as in `IPForMLSumcheck::prover_init`, products that are zero everywhere are dropped
+        let is_zero: Vec<bool> = polynomial
+            .tables
+            .iter()
+            .map(|table| is_zero_table(table))
+            .collect();
+        let products = nonzero_products(&polynomial.products, &is_zero);
+
+        let mut tables = polynomial.tables.clone();
+        let mut size = polynomial.size()?;
+        let mut prover_msgs = Vec::with_capacity(polynomial.num_variables);
+        let mut randomness = Vec::with_capacity(polynomial.num_variables);
+        for _ in 0..polynomial.num_variables {
+            let degree = round_degree(k, &products, &tables);
+            let weights = &weights[..=degree];
+            let mut evaluations = vec![F::zero(); degree + 1];
+            let mut table_evaluations = vec![vec![F::zero(); degree + 1]; tables.len()];
+            for rest in 0..size / k {
+                for (table, table_evaluations) in tables.iter().zip(table_evaluations.iter_mut()) {
+                    let slice = &table[rest * k..(rest + 1) * k];
+                    for (w, e) in weights.iter().zip(table_evaluations.iter_mut()) {
+                        *e = w.iter().zip(slice).map(|(w, v)| *w * v).sum();
+                    }
+                }
+                for (coefficient, products) in &products {
+                    for (t, e) in evaluations.iter_mut().enumerate() {
+                        *e += products
+                            .iter()
+                            .fold(*coefficient, |acc, &j| acc * table_evaluations[j][t]);
+                    }
+                }
+            }
+            let prover_msg = ProverMsg { evaluations };
+            fs_rng.feed(&prover_msg)?;
+            prover_msgs.push(prover_msg);
+
+            let r = IPForMLSumcheck::sample_round(fs_rng).randomness;
+            let w = lagrange_weights(&polynomial.domain, r);
+            for table in tables.iter_mut() {
+                *table = fold(table, &w);
+            }
+            size /= k;
+            randomness.push(r);
+        }
+
+        Ok((prover_msgs, randomness))
+    }
+
+    /// verify the claimed sum over `domain^num_vars` using the proof
+    pub fn verify(
+        domain: &[F],
+        polynomial_info: &PolynomialInfo,
+        claimed_sum: F,
+        proof: &Proof<F>,
+    ) -> Result<SubClaim<F>, crate::Error> {
+        let mut fs_rng = Blake2b512Rng::setup();
+        fs_rng.feed(&domain.to_vec())?;
+        Self::verify_as_subprotocol(&mut fs_rng, domain, polynomial_info, claimed_sum, proof)
+    }
+
+    /// This function does the same thing as `verify`, but it uses a `FeedableRNG` as the
+    /// transcript. The domain is not fed into `fs_rng`.
+    pub fn verify_as_subprotocol(
+        fs_rng: &mut impl FeedableRNG<Error = crate::Error>,
+        domain: &[F],
+        polynomial_info: &PolynomialInfo,
+        claimed_sum: F,
+        proof: &Proof<F>,
+    ) -> Result<SubClaim<F>, crate::Error> {
+        check_domain(domain)?;
+        if proof.len() != polynomial_info.num_variables {
+            return Err(crate::Error::Reject(Some("Proof is incomplete.".into())));
+        }
+        fs_rng.feed(polynomial_info)?;
+
+        let degree_bound = degree_bound(domain.len(), polynomial_info);
+        let mut expected = claimed_sum;
+        let mut point = Vec::with_capacity(polynomial_info.num_variables);
+        for (i, prover_msg) in proof.iter().enumerate() {
+            let evaluations = &prover_msg.evaluations;
+            // the degree of a round is at least one and at most the degree bound
+            if evaluations.len() < 2 || evaluations.len() > degree_bound + 1 {
+                return Err(crate::Error::Reject(Some(ark_std::format!(
+                    "Prover message at round {} has an incorrect number of evaluations: {}.",
+                    i,
+                    evaluations.len()
+                ))));
+            }
+            let sum: F = domain
+                .iter()
+                .map(|h| interpolate_uni_poly(evaluations, *h))
+                .sum();
+            if sum != expected {
+                return Err(crate::Error::Reject(Some(ark_std::format!(
+                    "Prover message at round {} is not consistent with the claim: expected a sum over the domain of {}, received {}.",
+                    i,
+                    expected,
+                    sum
+                ))));
+            }
+            fs_rng.feed(prover_msg)?;
+            let r = IPForMLSumcheck::sample_round(fs_rng).randomness;
+            expected = interpolate_uni_poly(evaluations, r);
+            point.push(r);
+        }
+
+        Ok(SubClaim {
+            point,
+            expected_evaluation: expected,
+        })
+    }
+}
+
+/// Max degree of the round polynomials: each multiplicand has degree `k - 1` in each variable.
+fn degree_bound(k: usize, info: &PolynomialInfo) -> usize {
+    max(info.degree_bound() * (k - 1), 1)
+}
+
+/// Degree of the round polynomial for the first variable of `tables`.
+///
+/// As in the multilinear prover, a multiplicand whose table does not depend on the variable does
+/// not contribute to the degree, and the degree is at least one. For `k = 2`, this is the degree
+/// used by `IPForMLSumcheck::prove_round`.
+fn round_degree<F: Field>(k: usize, products: &[(F, Vec<usize>)], tables: &[Vec<F>]) -> usize {
+    let depends: Vec<bool> = tables
+        .iter()
+        .map(|table| depends_on_variable(table, k, 1))
+        .collect();
+    max_product_degree(products, &depends)
+        .map_or(0, |d| d * (k - 1))
+        .max(1)
+}
+
+fn check_domain<F: Field>(domain: &[F]) -> Result<(), crate::Error> {
+    let distinct = domain
+        .iter()
+        .enumerate()
+        .all(|(i, a)| domain[..i].iter().all(|b| a != b));
+    if domain.len() < 2 || !distinct {
+        return Err(crate::Error::OtherError(
+            "domain should have at least two distinct elements".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the Lagrange basis polynomials of `domain` evaluated at `r`.
+fn lagrange_weights<F: Field>(domain: &[F], r: F) -> Vec<F> {
+    domain
+        .iter()
+        .enumerate()
+        .map(|(i, h_i)| {
+            let (numerator, denominator) = domain
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .fold((F::one(), F::one()), |(n, d), (_, h_j)| {
+                    (n * (r - h_j), d * (*h_i - h_j))
+                });
+            numerator * denominator.inverse().unwrap()
+        })
+        .collect()
+}
+
+/// Fix the first variable of `table` using the Lagrange `weights` of the point.
+fn fold<F: Field>(table: &[F], weights: &[F]) -> Vec<F> {
+    table
+        .chunks(weights.len())
+        .map(|chunk| weights.iter().zip(chunk).map(|(w, v)| *w * v).sum())
+        .collect()
+}
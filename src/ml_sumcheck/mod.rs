@@ -1,26 +1,44 @@
 //! Sumcheck Protocol for multilinear extension
 
-use crate::ml_sumcheck::data_structures::{ListOfProductsOfPolynomials, PolynomialInfo};
+use crate::ml_sumcheck::data_structures::{
+    depends_on_variable, is_zero_table, max_product_degree, nonzero_products, EvaluationOracle,
+    ListOfProductsOfPolynomials, PolynomialInfo,
+};
 use crate::ml_sumcheck::protocol::prover::{ProverMsg, ProverState};
-use crate::ml_sumcheck::protocol::verifier::SubClaim;
+use crate::ml_sumcheck::protocol::verifier::{MaskedSubClaim, SubClaim};
 use crate::ml_sumcheck::protocol::IPForMLSumcheck;
-use crate::rng::{Blake2b512Rng, FeedableRNG};
+use crate::rng::{Blake2b512Rng, FeedableRNG, SampleFieldElements};
 use ark_ff::Field;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::cmp::max;
 use ark_std::marker::PhantomData;
 use ark_std::vec::Vec;
 
 pub mod protocol;
 
 pub mod data_structures;
+pub mod domain;
 #[cfg(test)]
 mod test;
 
 /// Sumcheck for products of multilinear polynomial
 pub struct MLSumcheck<F: Field>(#[doc(hidden)] PhantomData<F>);
 
+/// size in bytes of the length prefix of a serialized `Vec`
+pub(crate) const LENGTH_PREFIX_SIZE: usize = 8;
+
 /// proof generated by prover
 pub type Proof<F> = Vec<ProverMsg<F>>;
 
+/// proof generated by prover for a batch of polynomials
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct BatchProof<F: Field> {
+    /// proof of the sum of the random linear combination of the polynomials
+    pub proof: Proof<F>,
+    /// evaluation of each polynomial at the point of the final subclaim
+    pub evaluations: Vec<F>,
+}
+
 impl<F: Field> MLSumcheck<F> {
     /// extract sum from the proof
     pub fn extract_sum(proof: &Proof<F>) -> F {
@@ -44,6 +62,19 @@ impl<F: Field> MLSumcheck<F> {
         Self::prove_as_subprotocol(&mut fs_rng, polynomial).map(|r| r.0)
     }
 
+    /// This function does the same thing as `prove`, but additionally returns the verifier challenges
+    /// sampled during proving, in the order they were sampled.
+    ///
+    /// The challenges are the same as `ProverState::randomness`, and equal the point of the subclaim
+    /// produced by `verify`.
+    pub fn prove_with_transcript(
+        polynomial: &ListOfProductsOfPolynomials<F>,
+    ) -> Result<(Proof<F>, Vec<F>), crate::Error> {
+        let mut fs_rng = Blake2b512Rng::setup();
+        Self::prove_as_subprotocol(&mut fs_rng, polynomial)
+            .map(|(proof, prover_state)| (proof, prover_state.randomness))
+    }
+
     /// This function does the same thing as `prove`, but it uses a `FeedableRNG` as the transcript/to generate the
     /// verifier challenges. Additionally, it returns the prover's state in addition to the proof.
     /// Both of these allow this sumcheck to be better used as a part of a larger protocol.
@@ -69,6 +100,253 @@ impl<F: Field> MLSumcheck<F> {
         Ok((prover_msgs, prover_state))
     }
 
+    /// generate proof of the sum of `polynomial` over {0,1}^`num_vars`, masked by `mask`
+    ///
+    /// The sumcheck is run on `f + rho * g`, where `f` is `polynomial`, `g` is the caller-supplied
+    /// random masking polynomial `mask`, and `rho` is sampled from the Fiat-Shamir transcript after
+    /// the sum of `g` is fed into it. Returns the proof together with the sum of `g`, which the
+    /// verifier needs to check the masked claim.
+    pub fn prove_with_mask(
+        polynomial: &ListOfProductsOfPolynomials<F>,
+        mask: &ListOfProductsOfPolynomials<F>,
+    ) -> Result<(Proof<F>, F), crate::Error> {
+        if polynomial.num_variables != mask.num_variables {
+            return Err(crate::Error::OtherError(
+                "mask has a different number of variables".into(),
+            ));
+        }
+        let mask_sum = mask.sum_over_hypercube();
+
+        let mut fs_rng = Blake2b512Rng::setup();
+        fs_rng.feed(&polynomial.info())?;
+        fs_rng.feed(&mask.info())?;
+        fs_rng.feed(&mask_sum)?;
+        let rho = F::rand(&mut fs_rng);
+
+        let mut masked = polynomial.clone();
+        masked.merge(mask, rho);
+        let (proof, _) = Self::prove_as_subprotocol(&mut fs_rng, &masked)?;
+        Ok((proof, mask_sum))
+    }
+
+    /// verify the claimed sum using a proof generated by `prove_with_mask`
+    ///
+    /// The returned subclaim is for `f + rho * g`, and has to be unmasked using the evaluation of
+    /// the mask at the subclaim point.
+    pub fn verify_with_mask(
+        polynomial_info: &PolynomialInfo,
+        mask_info: &PolynomialInfo,
+        claimed_sum: F,
+        mask_sum: F,
+        proof: &Proof<F>,
+    ) -> Result<MaskedSubClaim<F>, crate::Error> {
+        if polynomial_info.num_variables != mask_info.num_variables {
+            return Err(crate::Error::OtherError(
+                "mask has a different number of variables".into(),
+            ));
+        }
+        let mut fs_rng = Blake2b512Rng::setup();
+        fs_rng.feed(polynomial_info)?;
+        fs_rng.feed(mask_info)?;
+        fs_rng.feed(&mask_sum)?;
+        let rho = F::rand(&mut fs_rng);
+
+        let masked_info = PolynomialInfo {
+            max_multiplicands: max(
+                polynomial_info.max_multiplicands,
+                mask_info.max_multiplicands,
+            ),
+            num_variables: polynomial_info.num_variables,
+        };
+        if proof.len() != masked_info.num_variables {
+            return Err(crate::Error::Reject(Some(ark_std::format!(
+                "Proof has {} round messages, expected {}.",
+                proof.len(),
+                masked_info.num_variables
+            ))));
+        }
+        fs_rng.feed(&masked_info)?;
+        let mut verifier_state = IPForMLSumcheck::verifier_init(&masked_info);
+        for prover_msg in proof {
+            fs_rng.feed(prover_msg)?;
+            let _verifier_msg = IPForMLSumcheck::verify_round(
+                (*prover_msg).clone(),
+                &mut verifier_state,
+                &mut fs_rng,
+            );
+        }
+
+        IPForMLSumcheck::check_and_generate_subclaim_with_mask(
+            verifier_state,
+            claimed_sum,
+            mask_sum,
+            rho,
+        )
+    }
+
+    /// generate a single proof for the sums of all `polynomials` over {0,1}^`num_vars`
+    ///
+    /// All polynomials must have the same number of variables. After the info and the sum of each
+    /// polynomial are fed into the transcript, random coefficients are sampled and the sumcheck is
+    /// run on the random linear combination of the polynomials. The proof also contains the
+    /// evaluation of each polynomial at the final point.
+    pub fn prove_batch(
+        polynomials: &[ListOfProductsOfPolynomials<F>],
+    ) -> Result<BatchProof<F>, crate::Error> {
+        let num_variables = Self::batch_num_variables(polynomials.iter().map(|p| p.info()))?;
+        let sums: Vec<F> = polynomials.iter().map(|p| p.sum_over_hypercube()).collect();
+
+        let mut fs_rng = Blake2b512Rng::setup();
+        let coefficients = Self::sample_batch_coefficients(
+            &mut fs_rng,
+            &polynomials.iter().map(|p| p.info()).collect::<Vec<_>>(),
+            &sums,
+        )?;
+
+        let mut combined = ListOfProductsOfPolynomials::new(num_variables);
+        for (polynomial, coefficient) in polynomials.iter().zip(coefficients) {
+            combined.merge(polynomial, coefficient);
+        }
+
+        let (proof, prover_state) = Self::prove_as_subprotocol(&mut fs_rng, &combined)?;
+        let evaluations = polynomials
+            .iter()
+            .map(|p| p.evaluate(&prover_state.randomness))
+            .collect();
+        Ok(BatchProof { proof, evaluations })
+    }
+
+    /// verify the claimed sums of a batch of polynomials using a proof generated by `prove_batch`
+    ///
+    /// Returns one subclaim for each polynomial. All subclaims share the same point.
+    pub fn verify_batch(
+        polynomial_infos: &[PolynomialInfo],
+        claimed_sums: &[F],
+        proof: &BatchProof<F>,
+    ) -> Result<Vec<SubClaim<F>>, crate::Error> {
+        let num_variables = Self::batch_num_variables(polynomial_infos.iter().cloned())?;
+        if claimed_sums.len() != polynomial_infos.len()
+            || proof.evaluations.len() != polynomial_infos.len()
+        {
+            return Err(crate::Error::Reject(Some(
+                "Number of claims does not match the number of polynomials.".into(),
+            )));
+        }
+
+        let mut fs_rng = Blake2b512Rng::setup();
+        let coefficients =
+            Self::sample_batch_coefficients(&mut fs_rng, polynomial_infos, claimed_sums)?;
+
+        let combined_info = PolynomialInfo {
+            max_multiplicands: polynomial_infos
+                .iter()
+                .map(|info| info.max_multiplicands)
+                .max()
+                .unwrap_or(0),
+            num_variables,
+        };
+        let combined_sum = coefficients
+            .iter()
+            .zip(claimed_sums)
+            .map(|(c, s)| *c * s)
+            .sum();
+        let subclaim =
+            Self::verify_as_subprotocol(&mut fs_rng, &combined_info, combined_sum, &proof.proof)?;
+
+        let combined_evaluation: F = coefficients
+            .iter()
+            .zip(&proof.evaluations)
+            .map(|(c, e)| *c * e)
+            .sum();
+        if combined_evaluation != subclaim.expected_evaluation {
+            return Err(crate::Error::Reject(Some(
+                "Evaluations are not consistent with the subclaim.".into(),
+            )));
+        }
+
+        Ok(proof
+            .evaluations
+            .iter()
+            .map(|&expected_evaluation| SubClaim {
+                point: subclaim.point.clone(),
+                expected_evaluation,
+            })
+            .collect())
+    }
+
+    /// check that a batch is non-empty and that all polynomials have the same number of variables
+    fn batch_num_variables(
+        mut infos: impl Iterator<Item = PolynomialInfo>,
+    ) -> Result<usize, crate::Error> {
+        let num_variables = infos
+            .next()
+            .ok_or_else(|| crate::Error::OtherError("empty batch".into()))?
+            .num_variables;
+        if infos.any(|info| info.num_variables != num_variables) {
+            return Err(crate::Error::OtherError(
+                "polynomials in a batch must have the same number of variables".into(),
+            ));
+        }
+        Ok(num_variables)
+    }
+
+    /// feed the infos and sums of a batch and sample one coefficient for each polynomial
+    fn sample_batch_coefficients(
+        fs_rng: &mut impl FeedableRNG<Error = crate::Error>,
+        infos: &[PolynomialInfo],
+        sums: &[F],
+    ) -> Result<Vec<F>, crate::Error> {
+        for info in infos {
+            fs_rng.feed(info)?;
+        }
+        fs_rng.feed(&sums.to_vec())?;
+        Ok(fs_rng.sample_field_elements(infos.len()))
+    }
+
+    /// Returns the size in bytes of the compressed serialization of the proof generated by
+    /// `prove` for `polynomial`, without generating or serializing the proof.
+    ///
+    /// The prover sends `d + 1` evaluations at a round of degree `d`, so the size is computed
+    /// from the degree of each round, which is the max number of multiplicands of a nonzero
+    /// product that depend on its variable. A round of the actual proof only has a lower degree if
+    /// a challenge happens to be a root of a nonzero polynomial, which has negligible probability.
+    ///
+    /// Each table is scanned once to find whether it is zero, and once for each variable to find
+    /// whether it depends on it. A scan stops at the first evaluations that differ, so it is short
+    /// for random tables, but a table that does not depend on most variables takes
+    /// `O(num_variables * 2^num_variables)` comparisons. Use `max_proof_size_bytes` for a bound
+    /// that does not depend on the tables.
+    pub fn proof_size_bytes(polynomial: &ListOfProductsOfPolynomials<F>) -> usize {
+        let field_size = F::zero().compressed_size();
+        let is_zero: Vec<bool> = polynomial
+            .flattened_ml_extensions
+            .iter()
+            .map(|m| is_zero_table(&m.evaluations))
+            .collect();
+        let products = nonzero_products(&polynomial.products, &is_zero);
+        let msgs_size: usize = (0..polynomial.num_variables)
+            .map(|round| {
+                let depends: Vec<bool> = polynomial
+                    .flattened_ml_extensions
+                    .iter()
+                    .map(|m| depends_on_variable(&m.evaluations, 2, 1 << round))
+                    .collect();
+                let degree = max_product_degree(&products, &depends).unwrap_or(0).max(1);
+                LENGTH_PREFIX_SIZE + (degree + 1) * field_size
+            })
+            .sum();
+        LENGTH_PREFIX_SIZE + msgs_size
+    }
+
+    /// Returns an upper bound on the size in bytes of the compressed serialization of a proof
+    /// for a polynomial with `polynomial_info`, reached when every round has degree
+    /// `polynomial_info.degree_bound()`.
+    pub fn max_proof_size_bytes(polynomial_info: &PolynomialInfo) -> usize {
+        let field_size = F::zero().compressed_size();
+        let msg_size = LENGTH_PREFIX_SIZE + (polynomial_info.degree_bound() + 1) * field_size;
+        LENGTH_PREFIX_SIZE + polynomial_info.num_variables * msg_size
+    }
+
     /// verify the claimed sum using the proof
     pub fn verify(
         polynomial_info: &PolynomialInfo,
@@ -79,6 +357,24 @@ impl<F: Field> MLSumcheck<F> {
         Self::verify_as_subprotocol(&mut fs_rng, polynomial_info, claimed_sum, proof)
     }
 
+    /// Extract the sum from the first prover message and verify the proof against it. Returns the
+    /// subclaim along with the extracted sum.
+    ///
+    /// This only checks that the proof is internally consistent: the sum is chosen by the prover,
+    /// so the caller must compare it against the sum it expects.
+    pub fn verify_extracted(
+        polynomial_info: &PolynomialInfo,
+        proof: &Proof<F>,
+    ) -> Result<(SubClaim<F>, F), crate::Error> {
+        if proof.is_empty() || proof[0].evaluations.len() < 2 {
+            return Err(crate::Error::Reject(Some(
+                "Proof is empty or its first message is incomplete.".into(),
+            )));
+        }
+        let sum = Self::extract_sum(proof);
+        Self::verify(polynomial_info, sum, proof).map(|subclaim| (subclaim, sum))
+    }
+
     /// This function does the same thing as `prove`, but it uses a `FeedableRNG` as the transcript/to generate the
     /// verifier challenges. This allows this sumcheck to be used as a part of a larger protocol.
     pub fn verify_as_subprotocol(
@@ -87,10 +383,16 @@ impl<F: Field> MLSumcheck<F> {
         claimed_sum: F,
         proof: &Proof<F>,
     ) -> Result<SubClaim<F>, crate::Error> {
+        if proof.len() != polynomial_info.num_variables {
+            return Err(crate::Error::Reject(Some(ark_std::format!(
+                "Proof has {} round messages, expected {}.",
+                proof.len(),
+                polynomial_info.num_variables
+            ))));
+        }
         fs_rng.feed(polynomial_info)?;
         let mut verifier_state = IPForMLSumcheck::verifier_init(polynomial_info);
-        for i in 0..polynomial_info.num_variables {
-            let prover_msg = proof.get(i).expect("proof is incomplete");
+        for prover_msg in proof {
             fs_rng.feed(prover_msg)?;
             let _verifier_msg =
                 IPForMLSumcheck::verify_round((*prover_msg).clone(), &mut verifier_state, fs_rng);
@@ -98,4 +400,22 @@ impl<F: Field> MLSumcheck<F> {
 
         IPForMLSumcheck::check_and_generate_subclaim(verifier_state, claimed_sum)
     }
+
+    /// verify the claimed sum using the proof, and check the resulting subclaim against `oracle`
+    ///
+    /// This is useful when the polynomial is not available to the verifier as an explicit table.
+    pub fn verify_with_oracle(
+        polynomial_info: &PolynomialInfo,
+        claimed_sum: F,
+        proof: &Proof<F>,
+        oracle: &impl EvaluationOracle<F>,
+    ) -> Result<SubClaim<F>, crate::Error> {
+        let subclaim = Self::verify(polynomial_info, claimed_sum, proof)?;
+        if !subclaim.verify_with_oracle(oracle) {
+            return Err(crate::Error::Reject(Some(
+                "Oracle evaluation does not match the subclaim.".into(),
+            )));
+        }
+        Ok(subclaim)
+    }
 }
@@ -1,20 +1,94 @@
 //! Prover
-use crate::ml_sumcheck::data_structures::ListOfProductsOfPolynomials;
+use crate::ml_sumcheck::data_structures::{
+    depends_on_variable, is_zero_table, max_product_degree, nonzero_products,
+    ListOfProductsOfPolynomials, PolynomialInfo,
+};
 use crate::ml_sumcheck::protocol::verifier::VerifierMsg;
 use crate::ml_sumcheck::protocol::IPForMLSumcheck;
+use crate::ml_sumcheck::Proof;
+use crate::rng::{Blake2b512Rng, FeedableRNG};
 use ark_ff::Field;
 use ark_poly::{DenseMultilinearExtension, MultilinearExtension};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rc::Rc;
 use ark_std::{cfg_iter_mut, vec::Vec};
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
 /// Prover Message
-#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct ProverMsg<F: Field> {
     /// evaluations on P(0), P(1), P(2), ...
     pub(crate) evaluations: Vec<F>,
 }
+
+impl<F: Field> ProverMsg<F> {
+    /// Convert the round polynomial to coefficient form.
+    ///
+    /// Returns the coefficients `c_0, ..., c_d` of `P(x) = \sum_i c_i x^i`, where `d + 1` is the
+    /// number of evaluations in this message.
+    pub fn to_coefficients(&self) -> Vec<F> {
+        let len = self.evaluations.len();
+        if len == 0 {
+            return Vec::new();
+        }
+
+        // `numerator = \prod_{j=0}^{len-1} (x - j)`, stored from the lowest degree
+        let mut numerator = vec![F::zero(); len + 1];
+        numerator[0] = F::one();
+        for degree in 0..len {
+            // multiply by `(x - j)` with `j = degree`
+            let j = F::from(degree as u64);
+            for k in (0..=degree).rev() {
+                let c = numerator[k];
+                numerator[k + 1] += c;
+                numerator[k] = -j * c;
+            }
+        }
+
+        // `factorials[i] = i!`
+        let mut factorials = Vec::with_capacity(len);
+        factorials.push(F::one());
+        for i in 1..len {
+            factorials.push(factorials[i - 1] * F::from(i as u64));
+        }
+
+        let mut coefficients = vec![F::zero(); len];
+        for (i, p_i) in self.evaluations.iter().enumerate() {
+            // `\prod_{j != i} (i - j) = i! * (len - 1 - i)! * (-1)^(len - 1 - i)`
+            let mut denominator = factorials[i] * factorials[len - 1 - i];
+            if (len - 1 - i) % 2 == 1 {
+                denominator = -denominator;
+            }
+            let scale = *p_i * denominator.inverse().unwrap();
+
+            // divide `numerator` by `(x - i)` to get `\prod_{j != i} (x - j)`
+            let i = F::from(i as u64);
+            let mut carry = F::zero();
+            for k in (0..len).rev() {
+                carry = numerator[k + 1] + i * carry;
+                coefficients[k] += scale * carry;
+            }
+        }
+        coefficients
+    }
+
+    /// Build a prover message from the coefficients `c_0, ..., c_d` of the round polynomial.
+    ///
+    /// This is the inverse of `to_coefficients`.
+    pub fn from_coefficients(coefficients: &[F]) -> Self {
+        let evaluations = (0..coefficients.len())
+            .map(|i| {
+                let x = F::from(i as u64);
+                coefficients
+                    .iter()
+                    .rev()
+                    .fold(F::zero(), |acc, c| acc * x + c)
+            })
+            .collect();
+        ProverMsg { evaluations }
+    }
+}
 /// Prover State
 pub struct ProverState<F: Field> {
     /// sampled randomness given by the verifier
@@ -52,7 +126,7 @@ impl<F: Field> IPForMLSumcheck<F> {
         }
 
         // create a deep copy of all unique MLExtensions
-        let flattened_ml_extensions = polynomial
+        let flattened_ml_extensions: Vec<DenseMultilinearExtension<F>> = polynomial
             .flattened_ml_extensions
             .iter()
             .map(|x| x.as_ref().clone())
@@ -60,7 +134,34 @@ impl<F: Field> IPForMLSumcheck<F> {
 
         ProverState {
             randomness: Vec::with_capacity(polynomial.num_variables),
-            list_of_products: polynomial.products.clone(),
+            list_of_products: products_to_prove(&polynomial.products, &flattened_ml_extensions),
+            flattened_ml_extensions,
+            num_vars: polynomial.num_variables,
+            max_multiplicands: polynomial.max_multiplicands,
+            round: 0,
+        }
+    }
+
+    /// initialize the prover to argue for the sum of polynomial over {0,1}^`num_vars`, taking
+    /// ownership of the polynomial
+    ///
+    /// Same as `prover_init`, but a multiplicand is moved into the prover state instead of being
+    /// copied when `polynomial` holds the only reference to it. Multiplicands that are still
+    /// shared elsewhere are copied.
+    pub fn prover_init_in_place(polynomial: ListOfProductsOfPolynomials<F>) -> ProverState<F> {
+        if polynomial.num_variables == 0 {
+            panic!("Attempt to prove a constant.")
+        }
+
+        let flattened_ml_extensions: Vec<DenseMultilinearExtension<F>> = polynomial
+            .flattened_ml_extensions
+            .into_iter()
+            .map(|x| Rc::try_unwrap(x).unwrap_or_else(|x| x.as_ref().clone()))
+            .collect();
+
+        ProverState {
+            randomness: Vec::with_capacity(polynomial.num_variables),
+            list_of_products: products_to_prove(&polynomial.products, &flattened_ml_extensions),
             flattened_ml_extensions,
             num_vars: polynomial.num_variables,
             max_multiplicands: polynomial.max_multiplicands,
@@ -68,6 +169,89 @@ impl<F: Field> IPForMLSumcheck<F> {
         }
     }
 
+    /// initialize the prover to argue for the sum of polynomial over the last `num_vars - prefix.len()`
+    /// variables, with the first `prefix.len()` variables fixed to `prefix`
+    ///
+    /// Each unique multiplicand is fixed at `prefix` once, instead of being copied first. The
+    /// prefix is recorded at the beginning of `randomness`, so that it ends up being the full
+    /// point at which the polynomial is evaluated.
+    ///
+    /// Returns an error if `prefix` fixes all variables.
+    pub fn prover_init_from_fixed(
+        polynomial: &ListOfProductsOfPolynomials<F>,
+        prefix: &[F],
+    ) -> Result<ProverState<F>, crate::Error> {
+        if prefix.len() >= polynomial.num_variables {
+            return Err(crate::Error::OtherError(
+                "prefix should leave at least one variable free".into(),
+            ));
+        }
+
+        let flattened_ml_extensions: Vec<DenseMultilinearExtension<F>> = polynomial
+            .flattened_ml_extensions
+            .iter()
+            .map(|x| x.fix_variables(prefix))
+            .collect();
+
+        let mut randomness = Vec::with_capacity(polynomial.num_variables);
+        randomness.extend_from_slice(prefix);
+
+        Ok(ProverState {
+            randomness,
+            list_of_products: products_to_prove(&polynomial.products, &flattened_ml_extensions),
+            flattened_ml_extensions,
+            num_vars: polynomial.num_variables - prefix.len(),
+            max_multiplicands: polynomial.max_multiplicands,
+            round: 0,
+        })
+    }
+
+    /// initialize the prover to argue for the sum of polynomial over the subcube
+    /// {0,1}^`num_vars - fixed.len()` x {`fixed`}, where the last `fixed.len()` variables are fixed
+    /// to the given boolean values
+    ///
+    /// Each unique multiplicand is restricted to the subcube by taking the contiguous block of its
+    /// table where the high variables match `fixed`, so no padded or partially evaluated
+    /// polynomial is materialized. The verifier runs over `num_vars - fixed.len()` variables, and
+    /// the point of its subclaim has to be extended by `fixed` to evaluate the polynomial.
+    ///
+    /// Returns an error if `fixed` fixes all variables.
+    pub fn sum_over_subcube(
+        polynomial: &ListOfProductsOfPolynomials<F>,
+        fixed: &[bool],
+    ) -> Result<ProverState<F>, crate::Error> {
+        if fixed.len() >= polynomial.num_variables {
+            return Err(crate::Error::OtherError(
+                "fixed variables should leave at least one variable free".into(),
+            ));
+        }
+
+        let num_vars = polynomial.num_variables - fixed.len();
+        let offset = fixed
+            .iter()
+            .enumerate()
+            .fold(0, |acc, (i, &b)| acc | ((b as usize) << (num_vars + i)));
+        let flattened_ml_extensions: Vec<DenseMultilinearExtension<F>> = polynomial
+            .flattened_ml_extensions
+            .iter()
+            .map(|x| {
+                DenseMultilinearExtension::from_evaluations_slice(
+                    num_vars,
+                    &x.evaluations[offset..offset + (1 << num_vars)],
+                )
+            })
+            .collect();
+
+        Ok(ProverState {
+            randomness: Vec::with_capacity(num_vars),
+            list_of_products: products_to_prove(&polynomial.products, &flattened_ml_extensions),
+            flattened_ml_extensions,
+            num_vars,
+            max_multiplicands: polynomial.max_multiplicands,
+            round: 0,
+        })
+    }
+
     /// receive message from verifier, generate prover message, and proceed to next round
     ///
     /// Main algorithm used is from section 3.2 of [XZZPS19](https://eprint.iacr.org/2019/317.pdf#subsection.3.2).
@@ -82,10 +266,9 @@ impl<F: Field> IPForMLSumcheck<F> {
             prover_state.randomness.push(msg.randomness);
 
             // fix argument
-            let i = prover_state.round;
-            let r = prover_state.randomness[i - 1];
+            let r = msg.randomness;
             cfg_iter_mut!(prover_state.flattened_ml_extensions).for_each(|multiplicand| {
-                *multiplicand = multiplicand.fix_variables(&[r]);
+                fix_first_variable_in_place(multiplicand, r);
             });
         } else if prover_state.round > 0 {
             panic!("verifier message is empty");
@@ -99,7 +282,7 @@ impl<F: Field> IPForMLSumcheck<F> {
 
         let i = prover_state.round;
         let nv = prover_state.num_vars;
-        let degree = prover_state.max_multiplicands; // the degree of univariate polynomial sent by prover at this round
+        let degree = round_degree(prover_state); // the degree of univariate polynomial sent by prover at this round
 
         #[cfg(not(feature = "parallel"))]
         let zeros = (vec![F::zero(); degree + 1], vec![F::zero(); degree + 1]);
@@ -151,4 +334,191 @@ impl<F: Field> IPForMLSumcheck<F> {
             evaluations: products_sum,
         }
     }
+
+    /// generate a proof of the sum of `f * g` over {0,1}^`num_vars`, where `f` and `g` are the
+    /// evaluation tables of two multilinear extensions. Returns the proof and the sum.
+    ///
+    /// This is a specialized prover for a product of two multiplicands, which works on the tables
+    /// directly instead of going through a `ListOfProductsOfPolynomials`. The proof is the same as
+    /// the one `MLSumcheck::prove` generates for the product of `f` and `g`, and is verified by
+    /// `MLSumcheck::verify` with `max_multiplicands = 2`.
+    pub fn prove_product_of_two(f: &[F], g: &[F]) -> Result<(Proof<F>, F), crate::Error> {
+        if f.len() != g.len() || f.len() < 2 || !f.len().is_power_of_two() {
+            return Err(crate::Error::OtherError(
+                "f and g should have the same length, which is a power of two larger than one"
+                    .into(),
+            ));
+        }
+        let num_variables = f.len().trailing_zeros() as usize;
+        let mut fs_rng = Blake2b512Rng::setup();
+        fs_rng.feed(&PolynomialInfo {
+            max_multiplicands: 2,
+            num_variables,
+        })?;
+
+        let mut f = f.to_vec();
+        let mut g = g.to_vec();
+        let mut prover_msgs = Vec::with_capacity(num_variables);
+        for _ in 0..num_variables {
+            let mut evaluations = vec![F::zero(); 3];
+            for (f_pair, g_pair) in f.chunks(2).zip(g.chunks(2)) {
+                let (f0, f1) = (f_pair[0], f_pair[1]);
+                let (g0, g1) = (g_pair[0], g_pair[1]);
+                evaluations[0] += f0 * g0;
+                evaluations[1] += f1 * g1;
+                evaluations[2] += (f1.double() - f0) * (g1.double() - g0);
+            }
+            // same degree as `prove_round`
+            if !(depends_on_variable(&f, 2, 1) && depends_on_variable(&g, 2, 1)) {
+                evaluations.truncate(2);
+            }
+            let prover_msg = ProverMsg { evaluations };
+            fs_rng.feed(&prover_msg)?;
+            prover_msgs.push(prover_msg);
+
+            let r = Self::sample_round(&mut fs_rng).randomness;
+            for table in [&mut f, &mut g] {
+                let half = table.len() / 2;
+                for b in 0..half {
+                    let left = table[b << 1];
+                    let right = table[(b << 1) + 1];
+                    table[b] = left + r * (right - left);
+                }
+                table.truncate(half);
+            }
+        }
+
+        let sum = prover_msgs[0].evaluations[0] + prover_msgs[0].evaluations[1];
+        Ok((prover_msgs, sum))
+    }
+
+    /// generate a proof of the sum of `weights * polynomial` over {0,1}^`num_vars`, where
+    /// `weights` is a public multilinear extension.
+    ///
+    /// The proof is the same as the one `MLSumcheck::prove` generates when `weights` is multiplied
+    /// into every product of `polynomial`, and is verified by `MLSumcheck::verify` with
+    /// `max_multiplicands` increased by one. The expected evaluation of the subclaim is
+    /// `weights(point) * polynomial(point)`. Instead of multiplying `weights` into each product,
+    /// the products are summed first and multiplied by `weights` once per point, and points where
+    /// `weights` vanishes on both ends are skipped.
+    pub fn prove_weighted(
+        polynomial: &ListOfProductsOfPolynomials<F>,
+        weights: &DenseMultilinearExtension<F>,
+    ) -> Result<Proof<F>, crate::Error> {
+        if weights.num_vars != polynomial.num_variables {
+            return Err(crate::Error::OtherError(
+                "weights should have the same number of variables as the polynomial".into(),
+            ));
+        }
+        let mut prover_state = Self::prover_init(polynomial);
+        let mut weights = weights.clone();
+        if is_zero_table(&weights.evaluations) {
+            prover_state.list_of_products.clear();
+        }
+        let mut fs_rng = Blake2b512Rng::setup();
+        fs_rng.feed(&PolynomialInfo {
+            max_multiplicands: polynomial.max_multiplicands + 1,
+            num_variables: polynomial.num_variables,
+        })?;
+
+        let mut prover_msgs = Vec::with_capacity(polynomial.num_variables);
+        for _ in 0..polynomial.num_variables {
+            let weights_depend = depends_on_variable(&weights.evaluations, 2, 1);
+            let degree = current_max_product_degree(&prover_state)
+                .map_or(0, |d| d + weights_depend as usize)
+                .max(1);
+
+            let mut evaluations = vec![F::zero(); degree + 1];
+            let mut products_sum = vec![F::zero(); degree + 1];
+            let mut product = vec![F::zero(); degree + 1];
+            for b in 0..weights.evaluations.len() / 2 {
+                let mut weight = weights[b << 1];
+                let weight_step = weights[(b << 1) + 1] - weight;
+                if weight.is_zero() && weight_step.is_zero() {
+                    continue;
+                }
+                products_sum.fill(F::zero());
+                for (coefficient, products) in &prover_state.list_of_products {
+                    product.fill(*coefficient);
+                    for &jth_product in products {
+                        let table = &prover_state.flattened_ml_extensions[jth_product];
+                        let mut start = table[b << 1];
+                        let step = table[(b << 1) + 1] - start;
+                        for p in product.iter_mut() {
+                            *p *= start;
+                            start += step;
+                        }
+                    }
+                    for (s, p) in products_sum.iter_mut().zip(product.iter()) {
+                        *s += p;
+                    }
+                }
+                for (e, s) in evaluations.iter_mut().zip(products_sum.iter()) {
+                    *e += weight * s;
+                    weight += weight_step;
+                }
+            }
+            let prover_msg = ProverMsg { evaluations };
+            fs_rng.feed(&prover_msg)?;
+            prover_msgs.push(prover_msg);
+
+            let r = Self::sample_round(&mut fs_rng).randomness;
+            prover_state.randomness.push(r);
+            fix_first_variable_in_place(&mut weights, r);
+            for multiplicand in prover_state.flattened_ml_extensions.iter_mut() {
+                fix_first_variable_in_place(multiplicand, r);
+            }
+        }
+
+        Ok(prover_msgs)
+    }
+}
+
+/// Returns the products that are not zero everywhere, which are the only ones the prover needs to
+/// evaluate at each round.
+fn products_to_prove<F: Field>(
+    products: &[(F, Vec<usize>)],
+    flattened_ml_extensions: &[DenseMultilinearExtension<F>],
+) -> Vec<(F, Vec<usize>)> {
+    let is_zero: Vec<bool> = flattened_ml_extensions
+        .iter()
+        .map(|m| is_zero_table(&m.evaluations))
+        .collect();
+    nonzero_products(products, &is_zero)
+}
+
+/// Compute the degree of the univariate polynomial sent by the prover at the current round.
+///
+/// A multiplicand whose table does not depend on the current variable does not contribute to the
+/// degree, so the degree of a product is the number of its multiplicands that do. The degree is at
+/// least one, so that the verifier always receives both P(0) and P(1).
+fn round_degree<F: Field>(prover_state: &ProverState<F>) -> usize {
+    current_max_product_degree(prover_state).unwrap_or(0).max(1)
+}
+
+/// Returns the max number of multiplicands depending on the current variable over all products,
+/// or `None` if there is no product.
+fn current_max_product_degree<F: Field>(prover_state: &ProverState<F>) -> Option<usize> {
+    let depends: Vec<bool> = prover_state
+        .flattened_ml_extensions
+        .iter()
+        .map(|m| depends_on_variable(&m.evaluations, 2, 1))
+        .collect();
+    max_product_degree(&prover_state.list_of_products, &depends)
+}
+
+/// Fix the first variable of `poly` to `r` in place, halving its evaluation table.
+///
+/// The result is the same as `poly.fix_variables(&[r])`, but the existing allocation is reused
+/// instead of allocating a new table at each round.
+pub fn fix_first_variable_in_place<F: Field>(poly: &mut DenseMultilinearExtension<F>, r: F) {
+    assert!(poly.num_vars > 0, "no variable left to fix");
+    let half = 1 << (poly.num_vars - 1);
+    for b in 0..half {
+        let left = poly.evaluations[b << 1];
+        let right = poly.evaluations[(b << 1) + 1];
+        poly.evaluations[b] = left + r * (right - left);
+    }
+    poly.evaluations.truncate(half);
+    poly.num_vars -= 1;
 }
@@ -1,8 +1,8 @@
 //! Verifier
-use crate::ml_sumcheck::data_structures::PolynomialInfo;
+use crate::ml_sumcheck::data_structures::{EvaluationOracle, PolynomialInfo};
 use crate::ml_sumcheck::protocol::prover::ProverMsg;
 use crate::ml_sumcheck::protocol::IPForMLSumcheck;
-use ark_ff::Field;
+use ark_ff::{batch_inversion, Field};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::rand::RngCore;
 use ark_std::vec::Vec;
@@ -18,12 +18,15 @@ pub struct VerifierMsg<F: Field> {
 pub struct VerifierState<F: Field> {
     round: usize,
     nv: usize,
-    max_multiplicands: usize,
+    /// max degree of the round polynomials
+    degree_bound: usize,
     finished: bool,
     /// a list storing the univariate polynomial in evaluation form sent by the prover at each round
     polynomials_received: Vec<Vec<F>>,
     /// a list storing the randomness sampled by the verifier at each round
     randomness: Vec<F>,
+    /// the values of the variables that were fixed before the sumcheck started
+    prefix: Vec<F>,
 }
 /// Subclaim when verifier is convinced
 pub struct SubClaim<F: Field> {
@@ -33,19 +36,76 @@ pub struct SubClaim<F: Field> {
     pub expected_evaluation: F,
 }
 
+/// Subclaim of a masked sumcheck, where the sum is taken over `f + rho * g` for a masking polynomial `g`
+pub struct MaskedSubClaim<F: Field> {
+    /// the multi-dimensional point that this multilinear extension is evaluated to
+    pub point: Vec<F>,
+    /// the expected evaluation of `f + rho * g`
+    pub expected_evaluation: F,
+    /// the masking scalar `rho`
+    pub rho: F,
+}
+
+impl<F: Field> MaskedSubClaim<F> {
+    /// Remove the mask given its evaluation `g(point)`, returning the subclaim for `f` alone.
+    pub fn unmask(self, mask_evaluation: F) -> SubClaim<F> {
+        SubClaim {
+            point: self.point,
+            expected_evaluation: self.expected_evaluation - self.rho * mask_evaluation,
+        }
+    }
+}
+
+impl<F: Field> SubClaim<F> {
+    /// Check the subclaim using `oracle`, i.e. whether the polynomial evaluated at `self.point` is
+    /// `self.expected_evaluation`.
+    pub fn verify_with_oracle(&self, oracle: &impl EvaluationOracle<F>) -> bool {
+        oracle.evaluate(&self.point) == self.expected_evaluation
+    }
+}
+
 impl<F: Field> IPForMLSumcheck<F> {
     /// initialize the verifier
     pub fn verifier_init(index_info: &PolynomialInfo) -> VerifierState<F> {
         VerifierState {
             round: 1,
             nv: index_info.num_variables,
-            max_multiplicands: index_info.max_multiplicands,
+            degree_bound: index_info.degree_bound(),
             finished: false,
             polynomials_received: Vec::with_capacity(index_info.num_variables),
             randomness: Vec::with_capacity(index_info.num_variables),
+            prefix: Vec::new(),
         }
     }
 
+    /// initialize the verifier for a sum over the last `num_variables - prefix.len()` variables,
+    /// with the first `prefix.len()` variables fixed to `prefix`
+    ///
+    /// `index_info` describes the polynomial before fixing the prefix. The subclaim generated at
+    /// the end contains the full point, starting with `prefix`.
+    ///
+    /// Returns an error if `prefix` fixes all variables.
+    pub fn verifier_init_with_prefix(
+        index_info: &PolynomialInfo,
+        prefix: &[F],
+    ) -> Result<VerifierState<F>, crate::Error> {
+        if prefix.len() >= index_info.num_variables {
+            return Err(crate::Error::OtherError(
+                "prefix should leave at least one variable free".into(),
+            ));
+        }
+        let nv = index_info.num_variables - prefix.len();
+        Ok(VerifierState {
+            round: 1,
+            nv,
+            degree_bound: index_info.degree_bound(),
+            finished: false,
+            polynomials_received: Vec::with_capacity(nv),
+            randomness: Vec::with_capacity(nv),
+            prefix: prefix.to_vec(),
+        })
+    }
+
     /// Run verifier at current round, given prover message
     ///
     /// Normally, this function should perform actual verification. Instead, `verify_round` only samples
@@ -101,25 +161,56 @@ impl<F: Field> IPForMLSumcheck<F> {
         }
         for i in 0..verifier_state.nv {
             let evaluations = &verifier_state.polynomials_received[i];
-            if evaluations.len() != verifier_state.max_multiplicands + 1 {
-                panic!("incorrect number of evaluations");
+            // the prover sends `d + 1` evaluations for a round polynomial of degree `d`, where
+            // `d` is at least one and at most the degree bound.
+            if evaluations.len() < 2 || evaluations.len() > verifier_state.degree_bound + 1 {
+                return Err(crate::Error::Reject(Some(ark_std::format!(
+                    "Prover message at round {} has an incorrect number of evaluations: {}.",
+                    i,
+                    evaluations.len()
+                ))));
             }
             let p0 = evaluations[0];
             let p1 = evaluations[1];
             if p0 + p1 != expected {
-                return Err(crate::Error::Reject(Some(
-                    "Prover message is not consistent with the claim.".into(),
-                )));
+                return Err(crate::Error::Reject(Some(ark_std::format!(
+                    "Prover message at round {} is not consistent with the claim: expected P(0) + P(1) = {}, received {}.",
+                    i,
+                    expected,
+                    p0 + p1
+                ))));
             }
             expected = interpolate_uni_poly(evaluations, verifier_state.randomness[i]);
         }
 
+        let mut point = verifier_state.prefix;
+        point.extend(verifier_state.randomness);
         Ok(SubClaim {
-            point: verifier_state.randomness,
+            point,
             expected_evaluation: expected,
         })
     }
 
+    /// verify the sumcheck phase of a masked sumcheck, and generate the masked subclaim
+    ///
+    /// The prover argued for the sum of `f + rho * g`, so the claim checked is
+    /// `asserted_sum + rho * mask_sum`. The returned subclaim has to be unmasked using the
+    /// evaluation of `g` at the final point.
+    pub fn check_and_generate_subclaim_with_mask(
+        verifier_state: VerifierState<F>,
+        asserted_sum: F,
+        mask_sum: F,
+        rho: F,
+    ) -> Result<MaskedSubClaim<F>, crate::Error> {
+        let subclaim =
+            Self::check_and_generate_subclaim(verifier_state, asserted_sum + rho * mask_sum)?;
+        Ok(MaskedSubClaim {
+            point: subclaim.point,
+            expected_evaluation: subclaim.expected_evaluation,
+            rho,
+        })
+    }
+
     /// simulate a verifier message without doing verification
     ///
     /// Given the same calling context, `random_oracle_round` output exactly the same message as
@@ -139,10 +230,7 @@ impl<F: Field> IPForMLSumcheck<F> {
 pub(crate) fn interpolate_uni_poly<F: Field>(p_i: &[F], eval_at: F) -> F {
     let len = p_i.len();
 
-    let mut evals = vec![];
-
     let mut prod = eval_at;
-    evals.push(eval_at);
 
     //`prod = \prod_{j} (eval_at - j)`
     // we return early if 0 <= eval_at <  len, i.e. if the desired value has been passed
@@ -152,17 +240,125 @@ pub(crate) fn interpolate_uni_poly<F: Field>(p_i: &[F], eval_at: F) -> F {
             return p_i[i - 1];
         }
         check += F::one();
-
-        let tmp = eval_at - check;
-        evals.push(tmp);
-        prod *= tmp;
+        prod *= eval_at - check;
     }
 
     if eval_at == check {
         return p_i[len - 1];
     }
 
+    // the fractions are visited from `i = len - 1` down to 0, and `check` is `i` at each step
     let mut res = F::zero();
+    for_each_barycentric_fraction::<F>(len, |i, numerator, denominator| {
+        res += p_i[i] * prod * numerator / (denominator * (eval_at - check));
+        check -= F::one();
+    });
+
+    res
+}
+
+/// interpolate the *unique* univariate polynomial of degree *at most*
+/// p_i.len()-1 passing through the y-values in p_i at x = 0,..., p_i.len()-1
+/// and evaluate this polynomial at `eval_at`, using a precomputed table of factorials.
+///
+/// `factorials[k]` should be `k!` for all `k < p_i.len()`. When many polynomials of the same
+/// degree are interpolated, this avoids recomputing `field_factorial(p_i.len() - 1)` for each of
+/// them. The result is the same as `interpolate_uni_poly`.
+pub fn interpolate_uni_poly_with_factorials<F: Field>(
+    p_i: &[F],
+    eval_at: F,
+    factorials: &[F],
+) -> F {
+    let len = p_i.len();
+    assert!(factorials.len() >= len, "factorial table is too short");
+
+    // `evals[j] = eval_at - j`, and we return early if the desired value has been passed
+    let mut evals = Vec::with_capacity(len);
+    let mut check = F::zero();
+    for p in p_i {
+        if eval_at == check {
+            return *p;
+        }
+        evals.push(eval_at - check);
+        check += F::one();
+    }
+    let prod: F = evals.iter().product();
+
+    // \prod_{j!=i} (i-j) = i! * (len-1-i)! * (-1)^(len-1-i)
+    let mut denoms: Vec<F> = (0..len)
+        .map(|i| {
+            let denom = factorials[i] * factorials[len - 1 - i] * evals[i];
+            if (len - 1 - i) % 2 == 1 {
+                -denom
+            } else {
+                denom
+            }
+        })
+        .collect();
+    batch_inversion(&mut denoms);
+
+    prod * p_i.iter().zip(&denoms).map(|(p, inv)| *p * inv).sum::<F>()
+}
+
+/// interpolate the *unique* univariate polynomial of degree *at most*
+/// p_i.len()-1 passing through the y-values in p_i at x = 0,..., p_i.len()-1
+/// and evaluate this polynomial at each point of `eval_ats`.
+///
+/// The barycentric weights `1 / \prod_{j!=i} (i-j)` only depend on `p_i.len()`, so they are
+/// computed once and reused for all points. The result is the same as calling
+/// `interpolate_uni_poly` for each point.
+pub fn interpolate_uni_poly_multi<F: Field>(p_i: &[F], eval_ats: &[F]) -> Vec<F> {
+    let len = p_i.len();
+    let weights = barycentric_weights::<F>(len);
+
+    eval_ats
+        .iter()
+        .map(|&eval_at| {
+            // `evals[j] = eval_at - j`, and we return early if the desired value has been passed
+            let mut evals = Vec::with_capacity(len);
+            let mut check = F::zero();
+            for p in p_i {
+                if eval_at == check {
+                    return *p;
+                }
+                evals.push(eval_at - check);
+                check += F::one();
+            }
+            let prod: F = evals.iter().product();
+            batch_inversion(&mut evals);
+
+            prod * p_i
+                .iter()
+                .zip(&weights)
+                .zip(&evals)
+                .map(|((p, w), inv)| *p * w * inv)
+                .sum::<F>()
+        })
+        .collect()
+}
+
+/// compute the barycentric weights `1 / \prod_{j!=i} (i-j)` for the points 0, ..., len-1
+fn barycentric_weights<F: Field>(len: usize) -> Vec<F> {
+    let mut numerators = vec![F::zero(); len];
+    let mut denominators = vec![F::zero(); len];
+    for_each_barycentric_fraction::<F>(len, |i, numerator, denominator| {
+        numerators[i] = numerator;
+        denominators[i] = denominator;
+    });
+    batch_inversion(&mut denominators);
+    numerators
+        .iter()
+        .zip(&denominators)
+        .map(|(n, d)| *n * d)
+        .collect()
+}
+
+/// compute `\prod_{j!=i} (i-j)` for the points 0, ..., len-1, as fractions
+/// `denominator / numerator`, so that the weight of `i` is `numerator / denominator`, and call
+/// `f(i, numerator, denominator)` for each `i` from `len - 1` down to 0.
+///
+/// Nothing is allocated, so callers that only need each weight once can consume them directly.
+fn for_each_barycentric_fraction<F: Field>(len: usize, mut f: impl FnMut(usize, F, F)) {
     // we want to compute \prod (j!=i) (i-j) for a given i
     //
     // we start from the last step, which is
@@ -188,8 +384,8 @@ pub(crate) fn interpolate_uni_poly<F: Field>(p_i: &[F], eval_at: F) -> F {
     //  - for len <= 20 with i64
     //  - for len <= 33 with i128
     //  - for len >  33 with BigInt
-    if p_i.len() <= 20 {
-        let last_denom = F::from(u64_factorial(len - 1));
+    if len <= 20 {
+        let last_denom = F::from(u64_factorial(len.saturating_sub(1)));
         let mut ratio_numerator = 1i64;
         let mut ratio_enumerator = 1u64;
 
@@ -199,9 +395,7 @@ pub(crate) fn interpolate_uni_poly<F: Field>(p_i: &[F], eval_at: F) -> F {
             } else {
                 F::from(ratio_numerator as u64)
             };
-
-            res += p_i[i] * prod * F::from(ratio_enumerator)
-                / (last_denom * ratio_numerator_f * evals[i]);
+            f(i, F::from(ratio_enumerator), last_denom * ratio_numerator_f);
 
             // compute ratio for the next step which is current_ratio * -(len-i)/i
             if i != 0 {
@@ -209,7 +403,7 @@ pub(crate) fn interpolate_uni_poly<F: Field>(p_i: &[F], eval_at: F) -> F {
                 ratio_enumerator *= i as u64;
             }
         }
-    } else if p_i.len() <= 33 {
+    } else if len <= 33 {
         let last_denom = F::from(u128_factorial(len - 1));
         let mut ratio_numerator = 1i128;
         let mut ratio_enumerator = 1u128;
@@ -220,9 +414,7 @@ pub(crate) fn interpolate_uni_poly<F: Field>(p_i: &[F], eval_at: F) -> F {
             } else {
                 F::from(ratio_numerator as u128)
             };
-
-            res += p_i[i] * prod * F::from(ratio_enumerator)
-                / (last_denom * ratio_numerator_f * evals[i]);
+            f(i, F::from(ratio_enumerator), last_denom * ratio_numerator_f);
 
             // compute ratio for the next step which is current_ratio * -(len-i)/i
             if i != 0 {
@@ -237,7 +429,7 @@ pub(crate) fn interpolate_uni_poly<F: Field>(p_i: &[F], eval_at: F) -> F {
         let mut denom_down = F::one();
 
         for i in (0..len).rev() {
-            res += p_i[i] * prod * denom_down / (denom_up * evals[i]);
+            f(i, denom_down, denom_up);
 
             // compute denom for the next step is -current_denom * (len-i)/i
             if i != 0 {
@@ -246,8 +438,6 @@ pub(crate) fn interpolate_uni_poly<F: Field>(p_i: &[F], eval_at: F) -> F {
             }
         }
     }
-
-    res
 }
 
 /// compute the factorial(a) = 1 * 2 * ... * a
@@ -282,7 +472,9 @@ fn u64_factorial(a: usize) -> u64 {
 
 #[cfg(test)]
 mod test {
-    use crate::ml_sumcheck::protocol::verifier::interpolate_uni_poly;
+    use crate::ml_sumcheck::protocol::verifier::{
+        interpolate_uni_poly, interpolate_uni_poly_multi, interpolate_uni_poly_with_factorials,
+    };
     use ark_poly::univariate::DensePolynomial;
     use ark_poly::DenseUVPolynomial;
     use ark_poly::Polynomial;
@@ -326,8 +518,55 @@ mod test {
         // we are already passing, i.e. in the range 0 <= x < len(values) - 1
         let evals = vec![0, 1, 4, 9]
             .into_iter()
-            .map(|i| F::from(i))
+            .map(F::from)
             .collect::<Vec<F>>();
         assert_eq!(interpolate_uni_poly(&evals, F::from(3)), F::from(9));
     }
+
+    #[test]
+    fn test_interpolation_multi() {
+        let mut prng = ark_std::test_rng();
+
+        for degree in [5, 21, 40] {
+            let poly = DensePolynomial::<F>::rand(degree, &mut prng);
+            let evals = (0..=degree)
+                .map(|i| poly.evaluate(&F::from(i as u64)))
+                .collect::<Vec<F>>();
+            let mut queries = (0..10).map(|_| F::rand(&mut prng)).collect::<Vec<F>>();
+            queries.push(F::from(0u64));
+            queries.push(F::from(degree as u64));
+
+            let results = interpolate_uni_poly_multi(&evals, &queries);
+            assert_eq!(results.len(), queries.len());
+            for (query, result) in queries.iter().zip(results) {
+                assert_eq!(result, interpolate_uni_poly(&evals, *query));
+                assert_eq!(result, poly.evaluate(query));
+            }
+        }
+    }
+
+    #[test]
+    fn test_interpolation_with_factorials() {
+        let mut prng = ark_std::test_rng();
+        let mut factorials = vec![F::from(1u64)];
+        for k in 1..65u64 {
+            factorials.push(factorials[k as usize - 1] * F::from(k));
+        }
+
+        for degree in [5, 34, 50, 64] {
+            let poly = DensePolynomial::<F>::rand(degree, &mut prng);
+            let evals = (0..=degree as u64)
+                .map(|i| poly.evaluate(&F::from(i)))
+                .collect::<Vec<F>>();
+            let query = F::rand(&mut prng);
+            assert_eq!(
+                interpolate_uni_poly_with_factorials(&evals, query, &factorials),
+                interpolate_uni_poly(&evals, query)
+            );
+            assert_eq!(
+                interpolate_uni_poly_with_factorials(&evals, F::from(3u64), &factorials),
+                evals[3]
+            );
+        }
+    }
 }
@@ -1,10 +1,18 @@
-use crate::ml_sumcheck::data_structures::ListOfProductsOfPolynomials;
+use crate::ml_sumcheck::data_structures::{
+    add_poly, eq_extension, eq_mle, eval_at_batch, fix_last_variables, from_slice_accumulating,
+    from_sparse_pairs, hadamard, ListOfProductsOfPolynomials, PolynomialInfo,
+};
+use crate::ml_sumcheck::domain::{DomainPolynomial, DomainSumcheck};
+use crate::ml_sumcheck::protocol::prover::{fix_first_variable_in_place, ProverMsg, ProverState};
+use crate::ml_sumcheck::protocol::verifier::VerifierState;
 use crate::ml_sumcheck::protocol::IPForMLSumcheck;
 use crate::ml_sumcheck::MLSumcheck;
 use crate::rng::Blake2b512Rng;
 use crate::rng::FeedableRNG;
-use ark_ff::Field;
-use ark_poly::{DenseMultilinearExtension, MultilinearExtension};
+use ark_ff::{Field, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly::{DenseMultilinearExtension, DenseUVPolynomial, MultilinearExtension, Polynomial};
+use ark_serialize::CanonicalSerialize;
 use ark_std::rand::Rng;
 use ark_std::rand::RngCore;
 use ark_std::rc::Rc;
@@ -54,7 +62,7 @@ fn random_list_of_products<F: Field, R: RngCore>(
         let num_multiplicands = rng.gen_range(num_multiplicands_range.0..num_multiplicands_range.1);
         let (product, product_sum) = random_product(nv, num_multiplicands, rng);
         let coefficient = F::rand(rng);
-        poly.add_product(product.into_iter(), coefficient);
+        poly.add_product(product, coefficient);
         sum += product_sum * coefficient;
     }
 
@@ -267,3 +275,923 @@ fn test_shared_reference() {
         "wrong subclaim"
     );
 }
+
+#[test]
+fn test_fix_first_variable_in_place() {
+    let mut rng = test_rng();
+    let mut poly = DenseMultilinearExtension::<Fr>::rand(10, &mut rng);
+    let capacity = poly.evaluations.capacity();
+    for _ in 0..10 {
+        let r = Fr::rand(&mut rng);
+        let expected = poly.fix_variables(&[r]);
+        fix_first_variable_in_place(&mut poly, r);
+        assert_eq!(poly, expected);
+        assert_eq!(poly.evaluations.capacity(), capacity);
+    }
+}
+
+#[test]
+fn test_verify_with_oracle() {
+    let mut rng = test_rng();
+    let (poly, asserted_sum) = random_list_of_products::<Fr, _>(8, (2, 5), 3, &mut rng);
+    let poly_info = poly.info();
+    let proof = MLSumcheck::prove(&poly).expect("fail to prove");
+
+    let oracle = |point: &[Fr]| poly.evaluate(point);
+    let subclaim = MLSumcheck::verify_with_oracle(&poly_info, asserted_sum, &proof, &oracle)
+        .expect("fail to verify");
+    assert!(subclaim.verify_with_oracle(&poly));
+
+    let wrong_oracle = |point: &[Fr]| poly.evaluate(point) + Fr::ONE;
+    assert!(
+        MLSumcheck::verify_with_oracle(&poly_info, asserted_sum, &proof, &wrong_oracle).is_err()
+    );
+}
+
+#[test]
+fn test_masked_polynomial() {
+    let mut rng = test_rng();
+    let nv = 8;
+    let (poly, asserted_sum) = random_list_of_products::<Fr, _>(nv, (3, 6), 4, &mut rng);
+    // the mask has fewer multiplicands than the polynomial
+    let (mask, _) = random_list_of_products::<Fr, _>(nv, (1, 3), 2, &mut rng);
+
+    let (proof, mask_sum) = MLSumcheck::prove_with_mask(&poly, &mask).expect("fail to prove");
+    assert_eq!(mask_sum, mask.sum_over_hypercube());
+    let masked_subclaim =
+        MLSumcheck::verify_with_mask(&poly.info(), &mask.info(), asserted_sum, mask_sum, &proof)
+            .expect("fail to verify");
+    let mask_evaluation = mask.evaluate(&masked_subclaim.point);
+    let subclaim = masked_subclaim.unmask(mask_evaluation);
+    assert!(
+        poly.evaluate(&subclaim.point) == subclaim.expected_evaluation,
+        "wrong subclaim"
+    );
+
+    assert!(MLSumcheck::verify_with_mask(
+        &poly.info(),
+        &mask.info(),
+        asserted_sum + Fr::ONE,
+        mask_sum,
+        &proof
+    )
+    .is_err());
+
+    let truncated_proof = proof[..nv - 1].to_vec();
+    let result = MLSumcheck::verify_with_mask(
+        &poly.info(),
+        &mask.info(),
+        asserted_sum,
+        mask_sum,
+        &truncated_proof,
+    );
+    assert!(matches!(result, Err(crate::Error::Reject(_))));
+}
+
+#[test]
+fn test_batch_polynomials() {
+    let mut rng = test_rng();
+    let nv = 7;
+    let instances: Vec<_> = [(2, 4), (4, 7), (1, 2)]
+        .iter()
+        .map(|&range| random_list_of_products::<Fr, _>(nv, range, 3, &mut rng))
+        .collect();
+    let polys: Vec<_> = instances.iter().map(|(p, _)| p.clone()).collect();
+    let infos: Vec<_> = polys.iter().map(|p| p.info()).collect();
+    let sums: Vec<_> = instances.iter().map(|(_, s)| *s).collect();
+
+    let proof = MLSumcheck::prove_batch(&polys).expect("fail to prove");
+    let subclaims = MLSumcheck::verify_batch(&infos, &sums, &proof).expect("fail to verify");
+    assert_eq!(subclaims.len(), polys.len());
+    for (poly, subclaim) in polys.iter().zip(&subclaims) {
+        assert!(
+            poly.evaluate(&subclaim.point) == subclaim.expected_evaluation,
+            "wrong subclaim"
+        );
+    }
+
+    let mut wrong_sums = sums.clone();
+    wrong_sums[1] += Fr::ONE;
+    assert!(MLSumcheck::verify_batch(&infos, &wrong_sums, &proof).is_err());
+}
+
+#[test]
+fn test_merge() {
+    let mut rng = test_rng();
+    let nv = 6;
+    let ml_extensions: Vec<_> = (0..4)
+        .map(|_| Rc::new(DenseMultilinearExtension::<Fr>::rand(nv, &mut rng)))
+        .collect();
+    let mut poly = ListOfProductsOfPolynomials::new(nv);
+    poly.add_product(
+        vec![ml_extensions[0].clone(), ml_extensions[1].clone()],
+        Fr::rand(&mut rng),
+    );
+    let mut other = ListOfProductsOfPolynomials::new(nv);
+    other.add_product(
+        vec![
+            ml_extensions[1].clone(),
+            ml_extensions[2].clone(),
+            ml_extensions[3].clone(),
+        ],
+        Fr::rand(&mut rng),
+    );
+    other.add_product(vec![ml_extensions[0].clone()], Fr::rand(&mut rng));
+
+    let scalar = Fr::rand(&mut rng);
+    let mut merged = poly.clone();
+    merged.merge(&other, scalar);
+
+    assert_eq!(merged.flattened_ml_extensions.len(), ml_extensions.len());
+    assert_eq!(merged.products.len(), 3);
+    assert_eq!(merged.max_multiplicands, 3);
+    let point: Vec<_> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+    assert_eq!(
+        merged.evaluate(&point),
+        poly.evaluate(&point) + scalar * other.evaluate(&point)
+    );
+}
+
+#[test]
+fn test_fixed_prefix() {
+    let mut rng = test_rng();
+    let nv = 8;
+    let (poly, _) = random_list_of_products::<Fr, _>(nv, (2, 5), 4, &mut rng);
+    let prefix: Vec<_> = (0..3).map(|_| Fr::rand(&mut rng)).collect();
+
+    // the equivalent polynomial built by fixing the prefix manually
+    let mut fixed_poly = ListOfProductsOfPolynomials::new(nv - prefix.len());
+    for (coefficient, product) in &poly.products {
+        fixed_poly.add_product(
+            product
+                .iter()
+                .map(|&i| Rc::new(poly.flattened_ml_extensions[i].fix_variables(&prefix))),
+            *coefficient,
+        );
+    }
+    let asserted_sum = fixed_poly.sum_over_hypercube();
+
+    let run = |mut prover_state: ProverState<Fr>, mut verifier_state: VerifierState<Fr>| {
+        let mut rng = test_rng();
+        let mut verifier_msg = None;
+        for _ in 0..prover_state.num_vars {
+            let prover_message = IPForMLSumcheck::prove_round(&mut prover_state, &verifier_msg);
+            verifier_msg =
+                IPForMLSumcheck::verify_round(prover_message, &mut verifier_state, &mut rng);
+        }
+        IPForMLSumcheck::check_and_generate_subclaim(verifier_state, asserted_sum)
+            .expect("fail to generate subclaim")
+    };
+
+    let subclaim = run(
+        IPForMLSumcheck::prover_init_from_fixed(&poly, &prefix).unwrap(),
+        IPForMLSumcheck::verifier_init_with_prefix(&poly.info(), &prefix).unwrap(),
+    );
+    let manual_subclaim = run(
+        IPForMLSumcheck::prover_init(&fixed_poly),
+        IPForMLSumcheck::verifier_init(&fixed_poly.info()),
+    );
+
+    assert_eq!(subclaim.point.len(), nv);
+    assert_eq!(&subclaim.point[..prefix.len()], &prefix[..]);
+    assert_eq!(&subclaim.point[prefix.len()..], &manual_subclaim.point[..]);
+    assert_eq!(
+        subclaim.expected_evaluation,
+        manual_subclaim.expected_evaluation
+    );
+    assert!(
+        poly.evaluate(&subclaim.point) == subclaim.expected_evaluation,
+        "wrong subclaim"
+    );
+
+    let full_prefix: Vec<_> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+    assert!(IPForMLSumcheck::prover_init_from_fixed(&poly, &full_prefix).is_err());
+    assert!(IPForMLSumcheck::<Fr>::verifier_init_with_prefix(&poly.info(), &full_prefix).is_err());
+}
+
+#[test]
+fn test_varying_round_degrees() {
+    let mut rng = test_rng();
+    let nv = 6;
+    // `f` and `g` do not depend on the first variable, `h` does not depend on the last one
+    let f = Rc::new(DenseMultilinearExtension::from_evaluations_vec(
+        nv,
+        (0..1 << (nv - 1))
+            .flat_map(|_| {
+                let v = Fr::rand(&mut rng);
+                vec![v, v]
+            })
+            .collect(),
+    ));
+    let g = Rc::new(DenseMultilinearExtension::from_evaluations_vec(
+        nv,
+        (0..1 << (nv - 1))
+            .flat_map(|_| {
+                let v = Fr::rand(&mut rng);
+                vec![v, v]
+            })
+            .collect(),
+    ));
+    let h = {
+        let half: Vec<Fr> = (0..1 << (nv - 1)).map(|_| Fr::rand(&mut rng)).collect();
+        let mut evaluations = half.clone();
+        evaluations.extend(half);
+        Rc::new(DenseMultilinearExtension::from_evaluations_vec(
+            nv,
+            evaluations,
+        ))
+    };
+    let mut poly = ListOfProductsOfPolynomials::new(nv);
+    poly.add_product(vec![f, g, h.clone()], Fr::rand(&mut rng));
+    poly.add_product(vec![h], Fr::rand(&mut rng));
+    let asserted_sum = poly.sum_over_hypercube();
+
+    let proof = MLSumcheck::prove(&poly).expect("fail to prove");
+    assert_eq!(proof[0].evaluations.len(), 2);
+    assert_eq!(proof[1].evaluations.len(), 4);
+    assert_eq!(proof[nv - 1].evaluations.len(), 3);
+
+    let subclaim = MLSumcheck::verify(&poly.info(), asserted_sum, &proof).expect("fail to verify");
+    assert!(
+        poly.evaluate(&subclaim.point) == subclaim.expected_evaluation,
+        "wrong subclaim"
+    );
+}
+
+#[test]
+fn test_empty_polynomial() {
+    let nv = 4;
+    let poly = ListOfProductsOfPolynomials::<Fr>::new(nv);
+    let proof = MLSumcheck::prove(&poly).expect("fail to prove");
+    assert!(proof
+        .iter()
+        .all(|msg| msg.evaluations.iter().all(|e| e.is_zero())));
+    assert_eq!(MLSumcheck::extract_sum(&proof), Fr::zero());
+
+    let subclaim = MLSumcheck::verify(&poly.info(), Fr::zero(), &proof).expect("fail to verify");
+    assert_eq!(subclaim.point.len(), nv);
+    assert_eq!(poly.evaluate(&subclaim.point), subclaim.expected_evaluation);
+
+    assert!(MLSumcheck::verify(&poly.info(), Fr::ONE, &proof).is_err());
+}
+
+#[test]
+fn test_prover_msg_coefficients() {
+    let mut rng = test_rng();
+    for len in [1, 2, 3, 6, 21, 34, 41] {
+        let msg = ProverMsg {
+            evaluations: (0..len).map(|_| Fr::rand(&mut rng)).collect(),
+        };
+        let coefficients = msg.to_coefficients();
+        assert_eq!(coefficients.len(), len);
+
+        let poly = DensePolynomial::from_coefficients_slice(&coefficients);
+        for (i, evaluation) in msg.evaluations.iter().enumerate() {
+            assert_eq!(poly.evaluate(&Fr::from(i as u64)), *evaluation);
+        }
+        assert_eq!(ProverMsg::from_coefficients(&coefficients), msg);
+    }
+}
+
+#[test]
+fn test_prove_with_transcript() {
+    let mut rng = test_rng();
+    let (poly, asserted_sum) = random_list_of_products::<Fr, _>(9, (2, 5), 3, &mut rng);
+    let (proof, challenges) = MLSumcheck::prove_with_transcript(&poly).expect("fail to prove");
+    assert_eq!(challenges.len(), poly.num_variables);
+    assert_eq!(proof, MLSumcheck::prove(&poly).expect("fail to prove"));
+
+    let subclaim = MLSumcheck::verify(&poly.info(), asserted_sum, &proof).expect("fail to verify");
+    assert_eq!(challenges, subclaim.point);
+}
+
+#[test]
+fn test_fix_last_variables() {
+    let mut rng = test_rng();
+    let nv = 7;
+    let poly = DenseMultilinearExtension::<Fr>::rand(nv, &mut rng);
+    let point: Vec<_> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+    for k in 0..=nv {
+        let fixed = fix_last_variables(&poly, &point[k..]).unwrap();
+        assert_eq!(fixed.num_vars, k);
+        assert_eq!(fixed.evaluate(&point[..k].to_vec()), poly.evaluate(&point));
+    }
+    let too_long: Vec<_> = (0..nv + 1).map(|_| Fr::rand(&mut rng)).collect();
+    assert!(fix_last_variables(&poly, &too_long).is_err());
+}
+
+#[test]
+fn test_pointwise_operations() {
+    let mut rng = test_rng();
+    let nv = 6;
+    let a = DenseMultilinearExtension::<Fr>::rand(nv, &mut rng);
+    let b = DenseMultilinearExtension::<Fr>::rand(nv, &mut rng);
+
+    let product = hadamard(&a, &b).unwrap();
+    let sum = add_poly(&a, &b).unwrap();
+    assert_eq!(product.num_vars, nv);
+    assert_eq!(sum.num_vars, nv);
+    for x in 0..1 << nv {
+        assert_eq!(product[x], a[x] * b[x]);
+        assert_eq!(sum[x], a[x] + b[x]);
+    }
+
+    let c = DenseMultilinearExtension::<Fr>::rand(nv - 1, &mut rng);
+    assert!(hadamard(&a, &c).is_err());
+    assert!(add_poly(&a, &c).is_err());
+}
+
+#[test]
+fn test_from_slice_accumulating() {
+    let mut rng = test_rng();
+    let nv = 5;
+    let mut pairs: Vec<(usize, Fr)> = (0..20)
+        .map(|_| (rng.gen_range(0..1 << nv), Fr::rand(&mut rng)))
+        .collect();
+    // overlapping indices, one of which cancels out
+    pairs.push((3, Fr::ONE));
+    pairs.push((3, Fr::ONE));
+    pairs.push((7, Fr::ONE));
+    pairs.push((7, -Fr::ONE));
+
+    let poly = from_slice_accumulating(nv, &pairs).unwrap();
+    let mut dense = vec![Fr::zero(); 1 << nv];
+    for (index, value) in &pairs {
+        dense[*index] += value;
+    }
+    let dense = DenseMultilinearExtension::from_evaluations_vec(nv, dense);
+    assert_eq!(
+        poly.evaluations.len(),
+        dense.evaluations.iter().filter(|v| !v.is_zero()).count()
+    );
+    assert!(poly.evaluations.values().all(|v| !v.is_zero()));
+    let point: Vec<_> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+    assert_eq!(poly.evaluate(&point), dense.evaluate(&point));
+
+    assert!(from_slice_accumulating(nv, &[(1 << nv, Fr::ONE)]).is_err());
+}
+
+#[test]
+fn test_eval_at_batch() {
+    let mut rng = test_rng();
+    let nv = 8;
+    let poly = DenseMultilinearExtension::<Fr>::rand(nv, &mut rng);
+    let points: Vec<Vec<Fr>> = (0..20)
+        .map(|_| (0..nv).map(|_| Fr::rand(&mut rng)).collect())
+        .collect();
+    let point_refs: Vec<&[Fr]> = points.iter().map(|p| p.as_slice()).collect();
+
+    let evaluations = eval_at_batch(&poly, &point_refs).unwrap();
+    assert_eq!(evaluations.len(), points.len());
+    for (point, evaluation) in points.iter().zip(evaluations) {
+        assert_eq!(poly.evaluate(point), evaluation);
+    }
+
+    let short_point = vec![Fr::ONE; nv - 1];
+    assert!(eval_at_batch(&poly, &[&points[0], &short_point]).is_err());
+}
+
+#[test]
+fn test_sum_over_subcube() {
+    let mut rng = test_rng();
+    let nv = 7;
+    let (poly, _) = random_list_of_products::<Fr, _>(nv, (2, 4), 3, &mut rng);
+    let fixed = [true, false, true];
+    let k = nv - fixed.len();
+    let fixed_point: Vec<Fr> = fixed.iter().map(|&b| Fr::from(b)).collect();
+
+    let asserted_sum: Fr = (0..(1 << k))
+        .map(|x| {
+            let mut point: Vec<Fr> = (0..k).map(|i| Fr::from((x >> i) & 1 == 1)).collect();
+            point.extend_from_slice(&fixed_point);
+            poly.evaluate(&point)
+        })
+        .sum();
+
+    let mut prover_state = IPForMLSumcheck::sum_over_subcube(&poly, &fixed).unwrap();
+    let mut info = poly.info();
+    info.num_variables = k;
+    let mut verifier_state = IPForMLSumcheck::verifier_init(&info);
+    let mut verifier_msg = None;
+    for _ in 0..k {
+        let prover_message = IPForMLSumcheck::prove_round(&mut prover_state, &verifier_msg);
+        verifier_msg = IPForMLSumcheck::verify_round(prover_message, &mut verifier_state, &mut rng);
+    }
+    let subclaim = IPForMLSumcheck::check_and_generate_subclaim(verifier_state, asserted_sum)
+        .expect("fail to generate subclaim");
+
+    assert_eq!(subclaim.point.len(), k);
+    let mut point = subclaim.point.clone();
+    point.extend_from_slice(&fixed_point);
+    assert!(
+        poly.evaluate(&point) == subclaim.expected_evaluation,
+        "wrong subclaim"
+    );
+
+    assert!(IPForMLSumcheck::sum_over_subcube(&poly, &[false; 7]).is_err());
+}
+
+#[test]
+fn test_prover_init_in_place() {
+    let mut rng = test_rng();
+    let nv = 6;
+    let (poly, _) = random_list_of_products::<Fr, _>(nv, (2, 5), 3, &mut rng);
+    let (mut shared_poly, _) = random_list_of_products::<Fr, _>(nv, (2, 5), 3, &mut rng);
+    let shared = Rc::new(DenseMultilinearExtension::rand(nv, &mut rng));
+    shared_poly.add_product(vec![shared.clone()], Fr::ONE);
+
+    let run = |mut prover_state: ProverState<Fr>| {
+        let mut rng = test_rng();
+        let mut verifier_msg = None;
+        let mut prover_msgs = Vec::new();
+        for _ in 0..nv {
+            let prover_msg = IPForMLSumcheck::prove_round(&mut prover_state, &verifier_msg);
+            prover_msgs.push(prover_msg);
+            verifier_msg = Some(IPForMLSumcheck::sample_round(&mut rng));
+        }
+        prover_msgs
+    };
+
+    let expected = run(IPForMLSumcheck::prover_init(&poly));
+    let pointers: Vec<_> = poly
+        .flattened_ml_extensions
+        .iter()
+        .map(|x| x.evaluations.as_ptr())
+        .collect();
+    let prover_state = IPForMLSumcheck::prover_init_in_place(poly);
+    // unique multiplicands are moved, not copied
+    for (x, pointer) in prover_state.flattened_ml_extensions.iter().zip(pointers) {
+        assert_eq!(x.evaluations.as_ptr(), pointer);
+    }
+    assert_eq!(run(prover_state), expected);
+
+    // shared multiplicands are copied
+    let expected = run(IPForMLSumcheck::prover_init(&shared_poly));
+    let prover_state = IPForMLSumcheck::prover_init_in_place(shared_poly);
+    assert_ne!(
+        prover_state
+            .flattened_ml_extensions
+            .last()
+            .unwrap()
+            .evaluations
+            .as_ptr(),
+        shared.evaluations.as_ptr()
+    );
+    assert_eq!(run(prover_state), expected);
+}
+
+#[test]
+fn test_eq_mle() {
+    let mut rng = test_rng();
+    let nv = 5;
+    let t: Vec<Fr> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+    let x: Vec<Fr> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+    let expected: Fr = t
+        .iter()
+        .zip(x.iter())
+        .map(|(t_i, x_i)| Fr::ONE - t_i - x_i + (*t_i + t_i) * x_i)
+        .product();
+
+    assert_eq!(eq_mle(&t).evaluate(&x), expected);
+    let factors = eq_extension(&t);
+    assert_eq!(factors.len(), nv);
+    assert_eq!(
+        factors.iter().map(|f| f.evaluate(&x)).product::<Fr>(),
+        expected
+    );
+}
+
+#[test]
+fn test_too_many_evaluations_rejected() {
+    let mut rng = test_rng();
+    let nv = 4;
+    let (poly, asserted_sum) = random_list_of_products::<Fr, _>(nv, (2, 4), 2, &mut rng);
+    let info = poly.info();
+    let mut prover_state = IPForMLSumcheck::prover_init(&poly);
+    let mut verifier_state = IPForMLSumcheck::verifier_init(&info);
+    let mut verifier_msg = None;
+    for round in 0..nv {
+        let mut prover_message = IPForMLSumcheck::prove_round(&mut prover_state, &verifier_msg);
+        if round == 1 {
+            prover_message.evaluations = vec![Fr::ONE; info.degree_bound() + 2];
+        }
+        verifier_msg = IPForMLSumcheck::verify_round(prover_message, &mut verifier_state, &mut rng);
+    }
+    let result = IPForMLSumcheck::check_and_generate_subclaim(verifier_state, asserted_sum);
+    assert!(matches!(result, Err(crate::Error::Reject(_))));
+
+    // a truncated proof is rejected instead of panicking
+    let mut proof = MLSumcheck::prove(&poly).unwrap();
+    proof.pop();
+    let result = MLSumcheck::verify(&info, asserted_sum, &proof);
+    assert!(matches!(result, Err(crate::Error::Reject(_))));
+}
+
+#[test]
+fn test_verifier_cost() {
+    let info = |num_variables| PolynomialInfo {
+        max_multiplicands: 3,
+        num_variables,
+    };
+    let cost = info(10).verifier_cost();
+    assert_eq!(cost.interpolations, 10);
+    assert_eq!(cost.interpolation_degree, 3);
+
+    let double_cost = info(20).verifier_cost();
+    assert_eq!(double_cost.interpolations, 2 * cost.interpolations);
+    assert_eq!(
+        double_cost.field_multiplications,
+        2 * cost.field_multiplications
+    );
+    assert_eq!(double_cost.field_inversions, 2 * cost.field_inversions);
+
+    // tally the field operations of `interpolate_uni_poly` at a point outside 0..=d, step by step
+    let interpolation_count = |d: usize| {
+        let len = d + 1;
+        let mut multiplications = 0;
+        let mut inversions = 0;
+        // `prod = \prod_j (eval_at - j)`
+        multiplications += len - 1;
+        if len <= 33 {
+            // `last_denom * ratio_numerator` for each fraction
+            multiplications += len;
+        } else {
+            // `(len - 1)!`, then `denom_up` and `denom_down` for each step but the last
+            multiplications += len - 1;
+            multiplications += 2 * (len - 1);
+        }
+        // `p_i[i] * prod * numerator / (denominator * (eval_at - i))` for each evaluation
+        multiplications += 4 * len;
+        inversions += len;
+        (multiplications, inversions)
+    };
+    for max_multiplicands in 1..40 {
+        let num_variables = 7;
+        let cost = PolynomialInfo {
+            max_multiplicands,
+            num_variables,
+        }
+        .verifier_cost();
+        let (multiplications, inversions) = interpolation_count(max_multiplicands);
+        assert_eq!(cost.field_multiplications, num_variables * multiplications);
+        assert_eq!(cost.field_inversions, num_variables * inversions);
+    }
+}
+
+#[test]
+fn test_product_degrees() {
+    let mut rng = test_rng();
+    let nv = 4;
+    let (mut poly, _) = random_list_of_products::<Fr, _>(nv, (2, 5), 3, &mut rng);
+    let expected: Vec<usize> = poly.products.iter().map(|(_, p)| p.len()).collect();
+    assert_eq!(poly.product_degrees(), expected);
+
+    // a product whose second multiplicand does not depend on the first variable
+    let mut poly_2 = ListOfProductsOfPolynomials::new(nv);
+    let a = Rc::new(DenseMultilinearExtension::<Fr>::rand(nv, &mut rng));
+    let b: Vec<Fr> = (0..(1 << (nv - 1)))
+        .flat_map(|_| {
+            let v = Fr::rand(&mut rng);
+            vec![v, v]
+        })
+        .collect();
+    let b = Rc::new(DenseMultilinearExtension::from_evaluations_vec(nv, b));
+    poly_2.add_product(vec![a, b], Fr::ONE);
+    assert_eq!(poly_2.product_degrees(), vec![2]);
+    assert_eq!(poly_2.max_multiplicands_at_round(0, &[]), 1);
+    assert_eq!(poly_2.max_multiplicands_at_round(1, &[]), 2);
+    let r = Fr::rand(&mut rng);
+    assert_eq!(poly_2.max_multiplicands_at_round(1, &[r]), 2);
+
+    poly.merge(&poly_2, Fr::ONE);
+    assert_eq!(
+        poly.max_multiplicands_at_round(0, &[]),
+        poly.max_multiplicands
+    );
+}
+
+#[test]
+fn test_prove_product_of_two() {
+    let mut rng = test_rng();
+    let nv = 14;
+    let f = DenseMultilinearExtension::<Fr>::rand(nv, &mut rng);
+    let g = DenseMultilinearExtension::<Fr>::rand(nv, &mut rng);
+    let mut poly = ListOfProductsOfPolynomials::new(nv);
+    poly.add_product(vec![Rc::new(f.clone()), Rc::new(g.clone())], Fr::ONE);
+
+    let (proof, sum) =
+        IPForMLSumcheck::prove_product_of_two(&f.evaluations, &g.evaluations).unwrap();
+    assert_eq!(sum, poly.sum_over_hypercube());
+    assert_eq!(proof, MLSumcheck::prove(&poly).unwrap());
+    let subclaim = MLSumcheck::verify(&poly.info(), sum, &proof).expect("fail to verify");
+    assert!(
+        poly.evaluate(&subclaim.point) == subclaim.expected_evaluation,
+        "wrong subclaim"
+    );
+
+    assert!(IPForMLSumcheck::prove_product_of_two(&f.evaluations, &g.evaluations[1..]).is_err());
+}
+
+#[test]
+fn test_reject_reason_reports_round() {
+    let mut rng = test_rng();
+    let nv = 5;
+    let (poly, asserted_sum) = random_list_of_products::<Fr, _>(nv, (2, 4), 2, &mut rng);
+    let mut proof = MLSumcheck::prove(&poly).unwrap();
+    proof[3].evaluations[0] += Fr::ONE;
+    let result = MLSumcheck::verify(&poly.info(), asserted_sum, &proof);
+    match result {
+        Err(crate::Error::Reject(Some(reason))) => {
+            assert!(reason.contains("round 3"), "unexpected reason: {}", reason)
+        }
+        _ => panic!("corrupted proof should be rejected"),
+    }
+}
+
+#[test]
+fn test_domain_sumcheck() {
+    let mut rng = test_rng();
+    let nv = 3;
+    let domain: Vec<Fr> = (0..3u64).map(Fr::from).collect();
+    let mut poly = DomainPolynomial::new(domain.clone(), nv);
+    for num_multiplicands in [2, 3] {
+        let product: Vec<Vec<Fr>> = (0..num_multiplicands)
+            .map(|_| (0..27).map(|_| Fr::rand(&mut rng)).collect())
+            .collect();
+        poly.add_product(product, Fr::rand(&mut rng)).unwrap();
+    }
+    let asserted_sum = poly.sum_over_domain();
+
+    let proof = DomainSumcheck::prove(&poly).expect("fail to prove");
+    let subclaim = DomainSumcheck::verify(&domain, &poly.info(), asserted_sum, &proof)
+        .expect("fail to verify");
+    assert!(
+        poly.evaluate(&subclaim.point) == subclaim.expected_evaluation,
+        "wrong subclaim"
+    );
+    assert!(DomainSumcheck::verify(&domain, &poly.info(), asserted_sum + Fr::ONE, &proof).is_err());
+
+    // a polynomial without products sums to zero
+    let empty_poly = DomainPolynomial::new(domain.clone(), nv);
+    let proof = DomainSumcheck::prove(&empty_poly).expect("fail to prove");
+    let subclaim = DomainSumcheck::verify(&domain, &empty_poly.info(), Fr::zero(), &proof)
+        .expect("fail to verify");
+    assert!(subclaim.expected_evaluation.is_zero());
+
+    // malformed products are rejected
+    let mut poly = DomainPolynomial::new(domain.clone(), nv);
+    assert!(poly.add_product(Vec::new(), Fr::ONE).is_err());
+    assert!(poly.add_product(vec![vec![Fr::ONE; 26]], Fr::ONE).is_err());
+    let mut huge_poly = DomainPolynomial::new(domain.clone(), 64);
+    assert!(huge_poly.add_product(vec![vec![Fr::ONE]], Fr::ONE).is_err());
+    assert!(poly.products.is_empty() && huge_poly.products.is_empty());
+}
+
+#[test]
+fn test_domain_sumcheck_boolean() {
+    let mut rng = test_rng();
+    let nv = 5;
+    let (mut poly, mut asserted_sum) = random_list_of_products::<Fr, _>(nv, (2, 5), 3, &mut rng);
+    // a constant multiplicand lowers the degree of every round of its product
+    let constant = Fr::rand(&mut rng);
+    let random = DenseMultilinearExtension::rand(nv, &mut rng);
+    asserted_sum += constant * random.evaluations.iter().sum::<Fr>();
+    poly.add_product(
+        vec![
+            Rc::new(random),
+            Rc::new(DenseMultilinearExtension::from_evaluations_vec(
+                nv,
+                vec![constant; 1 << nv],
+            )),
+        ],
+        Fr::ONE,
+    );
+    let domain_poly = DomainPolynomial::from(&poly);
+    assert_eq!(domain_poly.sum_over_domain(), asserted_sum);
+
+    let (proof, point) =
+        DomainSumcheck::prove_as_subprotocol(&mut Blake2b512Rng::setup(), &domain_poly).unwrap();
+    let (expected_proof, prover_state) =
+        MLSumcheck::prove_as_subprotocol(&mut Blake2b512Rng::setup(), &poly).unwrap();
+    assert_eq!(proof, expected_proof);
+    assert_eq!(point, prover_state.randomness);
+
+    let subclaim = DomainSumcheck::verify_as_subprotocol(
+        &mut Blake2b512Rng::setup(),
+        &domain_poly.domain,
+        &poly.info(),
+        asserted_sum,
+        &proof,
+    )
+    .expect("fail to verify");
+    assert_eq!(subclaim.point, point);
+    assert!(
+        poly.evaluate(&subclaim.point) == subclaim.expected_evaluation,
+        "wrong subclaim"
+    );
+
+    // a product whose multiplicands are all constant has degree one
+    let mut constant_poly = ListOfProductsOfPolynomials::new(nv);
+    constant_poly.add_product(
+        vec![Rc::new(DenseMultilinearExtension::from_evaluations_vec(
+            nv,
+            vec![constant; 1 << nv],
+        ))],
+        Fr::ONE,
+    );
+    let constant_sum = constant_poly.sum_over_hypercube();
+    let (proof, _) = DomainSumcheck::prove_as_subprotocol(
+        &mut Blake2b512Rng::setup(),
+        &DomainPolynomial::from(&constant_poly),
+    )
+    .unwrap();
+    assert_eq!(proof, MLSumcheck::prove(&constant_poly).unwrap());
+    assert!(MLSumcheck::verify(&constant_poly.info(), constant_sum, &proof).is_ok());
+    assert!(DomainSumcheck::verify_as_subprotocol(
+        &mut Blake2b512Rng::setup(),
+        &[Fr::zero(), Fr::ONE],
+        &constant_poly.info(),
+        constant_sum,
+        &proof
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_fix_first_variable() {
+    let mut rng = test_rng();
+    let nv = 6;
+    let (mut poly, _) = random_list_of_products::<Fr, _>(nv, (2, 5), 3, &mut rng);
+    let shared = poly.flattened_ml_extensions[0].clone();
+    poly.add_product(vec![shared.clone(), shared], Fr::rand(&mut rng));
+
+    let r = Fr::rand(&mut rng);
+    let fixed = poly.fix_first_variable(r);
+    assert_eq!(fixed.num_variables, nv - 1);
+    assert_eq!(
+        fixed.flattened_ml_extensions.len(),
+        poly.flattened_ml_extensions.len()
+    );
+    for _ in 0..5 {
+        let point: Vec<Fr> = (0..nv - 1).map(|_| Fr::rand(&mut rng)).collect();
+        let mut full_point = vec![r];
+        full_point.extend_from_slice(&point);
+        assert_eq!(fixed.evaluate(&point), poly.evaluate(&full_point));
+    }
+}
+
+#[test]
+fn test_zero_factor_dropped() {
+    let mut rng = test_rng();
+    let nv = 5;
+    let (poly, _) = random_list_of_products::<Fr, _>(nv, (3, 5), 3, &mut rng);
+    let mut poly_with_zero = poly.clone();
+    poly_with_zero.add_product(
+        vec![
+            Rc::new(DenseMultilinearExtension::rand(nv, &mut rng)),
+            Rc::new(DenseMultilinearExtension::from_evaluations_vec(
+                nv,
+                vec![Fr::zero(); 1 << nv],
+            )),
+        ],
+        Fr::rand(&mut rng),
+    );
+
+    let prover_state = IPForMLSumcheck::prover_init(&poly_with_zero);
+    assert_eq!(prover_state.list_of_products, poly.products);
+    assert_eq!(
+        MLSumcheck::prove(&poly_with_zero).unwrap(),
+        MLSumcheck::prove(&poly).unwrap()
+    );
+
+    // all products dropped
+    let mut zero_poly = ListOfProductsOfPolynomials::new(nv);
+    zero_poly.add_product(
+        vec![Rc::new(DenseMultilinearExtension::from_evaluations_vec(
+            nv,
+            vec![Fr::zero(); 1 << nv],
+        ))],
+        Fr::ONE,
+    );
+    let proof = MLSumcheck::prove(&zero_poly).unwrap();
+    let subclaim = MLSumcheck::verify(&zero_poly.info(), Fr::zero(), &proof).unwrap();
+    assert!(subclaim.expected_evaluation.is_zero());
+}
+
+#[test]
+fn test_proof_size_bytes() {
+    let mut rng = test_rng();
+    for nv in 1..8 {
+        for num_multiplicands in 1..4 {
+            let (poly, _) =
+                random_list_of_products::<Fr, _>(nv, (1, num_multiplicands + 1), 2, &mut rng);
+            let proof = MLSumcheck::prove(&poly).unwrap();
+            assert_eq!(MLSumcheck::proof_size_bytes(&poly), proof.compressed_size());
+            assert_eq!(
+                MLSumcheck::<Fr>::max_proof_size_bytes(&poly.info()),
+                proof.compressed_size()
+            );
+        }
+    }
+
+    // rounds where a multiplicand does not depend on the variable have a lower degree
+    let nv = 3;
+    let constant = Rc::new(DenseMultilinearExtension::from_evaluations_vec(
+        nv,
+        vec![Fr::rand(&mut rng); 1 << nv],
+    ));
+    // depends on the first variable only
+    let first_variable = Rc::new(DenseMultilinearExtension::from_evaluations_vec(
+        nv,
+        (0..1 << nv).map(|i| Fr::from((i & 1) as u64)).collect(),
+    ));
+    let zero = Rc::new(DenseMultilinearExtension::from_evaluations_vec(
+        nv,
+        vec![Fr::zero(); 1 << nv],
+    ));
+    let mut random = || Rc::new(DenseMultilinearExtension::<Fr>::rand(nv, &mut rng));
+    let mut poly = ListOfProductsOfPolynomials::new(nv);
+    poly.add_product(vec![random(), constant.clone()], Fr::ONE);
+    let proof = MLSumcheck::prove(&poly).unwrap();
+    assert_eq!(MLSumcheck::proof_size_bytes(&poly), proof.compressed_size());
+    assert_eq!(proof.compressed_size(), 224);
+
+    poly.add_product(vec![random(), first_variable, constant], Fr::ONE);
+    poly.add_product(vec![random(), random(), random(), zero], Fr::ONE);
+    let proof = MLSumcheck::prove(&poly).unwrap();
+    assert_eq!(MLSumcheck::proof_size_bytes(&poly), proof.compressed_size());
+    assert!(proof.compressed_size() < MLSumcheck::<Fr>::max_proof_size_bytes(&poly.info()));
+}
+
+#[test]
+fn test_from_sparse_pairs() {
+    let mut rng = test_rng();
+    let nv = 5;
+    let pairs: Vec<(usize, Fr)> = [0, 3, 17, 31]
+        .iter()
+        .map(|&index| (index, Fr::rand(&mut rng)))
+        .collect();
+
+    let poly = from_sparse_pairs(nv, &pairs).unwrap();
+    let mut evaluations = vec![Fr::zero(); 1 << nv];
+    for (index, value) in &pairs {
+        evaluations[*index] = *value;
+    }
+    let expected = DenseMultilinearExtension::from_evaluations_vec(nv, evaluations);
+    assert_eq!(poly, expected);
+    let point: Vec<_> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+    assert_eq!(poly.evaluate(&point), expected.evaluate(&point));
+
+    assert!(from_sparse_pairs(nv, &[(1 << nv, Fr::ONE)]).is_err());
+    assert!(from_sparse_pairs(nv, &[(3, Fr::ONE), (3, Fr::ONE)]).is_err());
+}
+
+#[test]
+fn test_verify_extracted() {
+    let mut rng = test_rng();
+    let (poly, asserted_sum) = random_list_of_products::<Fr, _>(6, (2, 4), 3, &mut rng);
+    let info = poly.info();
+    let proof = MLSumcheck::prove(&poly).unwrap();
+
+    let (subclaim, sum) = MLSumcheck::verify_extracted(&info, &proof).unwrap();
+    assert_eq!(sum, asserted_sum);
+    let expected = MLSumcheck::verify(&info, MLSumcheck::extract_sum(&proof), &proof).unwrap();
+    assert_eq!(subclaim.point, expected.point);
+    assert_eq!(subclaim.expected_evaluation, expected.expected_evaluation);
+
+    assert!(MLSumcheck::<Fr>::verify_extracted(&info, &Vec::new()).is_err());
+    let truncated_proof = proof[..proof.len() - 1].to_vec();
+    let result = MLSumcheck::verify_extracted(&info, &truncated_proof);
+    assert!(matches!(result, Err(crate::Error::Reject(_))));
+}
+
+#[test]
+fn test_prove_weighted() {
+    let mut rng = test_rng();
+    let nv = 8;
+    let (poly, _) = random_list_of_products::<Fr, _>(nv, (2, 4), 3, &mut rng);
+    let weights = DenseMultilinearExtension::<Fr>::rand(nv, &mut rng);
+    let mut weighted_poly = ListOfProductsOfPolynomials::new(nv);
+    let shared_weights = Rc::new(weights.clone());
+    for (coefficient, products) in &poly.products {
+        let mut product: Vec<_> = products
+            .iter()
+            .map(|&i| poly.flattened_ml_extensions[i].clone())
+            .collect();
+        product.push(shared_weights.clone());
+        weighted_poly.add_product(product, *coefficient);
+    }
+
+    let proof = IPForMLSumcheck::prove_weighted(&poly, &weights).unwrap();
+    assert_eq!(proof, MLSumcheck::prove(&weighted_poly).unwrap());
+    let sum = MLSumcheck::extract_sum(&proof);
+    assert_eq!(sum, weighted_poly.sum_over_hypercube());
+    let subclaim = MLSumcheck::verify(&weighted_poly.info(), sum, &proof).expect("fail to verify");
+    assert_eq!(
+        subclaim.expected_evaluation,
+        weights.evaluate(&subclaim.point) * poly.evaluate(&subclaim.point)
+    );
+
+    let wrong_weights = DenseMultilinearExtension::<Fr>::rand(nv - 1, &mut rng);
+    assert!(IPForMLSumcheck::prove_weighted(&poly, &wrong_weights).is_err());
+}
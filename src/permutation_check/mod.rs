@@ -0,0 +1,138 @@
+//! Permutation check: proves that two multilinear extensions have the same multiset of
+//! evaluations over the boolean hypercube.
+//!
+//! For a random `gamma`, `f` and `g` are a permutation of each other (with high probability) if
+//! and only if
+//!
+//! $$\prod_{x}(f(x) + \gamma) = \prod_{x}(g(x) + \gamma)$$
+//!
+//! Both products are proven with `GrandProduct`.
+
+#[cfg(test)]
+mod test;
+
+use crate::grand_product::{GrandProduct, GrandProductProof};
+use crate::rng::FeedableRNG;
+use ark_ff::Field;
+use ark_poly::{DenseMultilinearExtension, Polynomial};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::marker::PhantomData;
+use ark_std::vec::Vec;
+
+/// Permutation check for two multilinear extensions
+pub struct PermutationCheck<F: Field>(#[doc(hidden)] PhantomData<F>);
+
+/// Proof generated by `PermutationCheck::prove`
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PermutationCheckProof<F: Field> {
+    /// product of `f(x) + gamma` over the hypercube
+    pub f_product: F,
+    /// grand product proof for `f + gamma`
+    pub f_proof: GrandProductProof<F>,
+    /// product of `g(x) + gamma` over the hypercube
+    pub g_product: F,
+    /// grand product proof for `g + gamma`
+    pub g_proof: GrandProductProof<F>,
+}
+
+/// Subclaim returned by `PermutationCheck::verify`, which is a claim about both inputs
+pub struct PermutationCheckSubClaim<F: Field> {
+    /// the point that `f` is evaluated to
+    pub f_point: Vec<F>,
+    /// the expected evaluation of `f`
+    pub f_expected_evaluation: F,
+    /// the point that `g` is evaluated to
+    pub g_point: Vec<F>,
+    /// the expected evaluation of `g`
+    pub g_expected_evaluation: F,
+}
+
+impl<F: Field> PermutationCheckSubClaim<F> {
+    /// Verify that the subclaim is true by evaluating `f` and `g`.
+    pub fn verify_subclaim(
+        &self,
+        f: &DenseMultilinearExtension<F>,
+        g: &DenseMultilinearExtension<F>,
+    ) -> bool {
+        f.num_vars == self.f_point.len()
+            && g.num_vars == self.g_point.len()
+            && f.evaluate(&self.f_point) == self.f_expected_evaluation
+            && g.evaluate(&self.g_point) == self.g_expected_evaluation
+    }
+}
+
+impl<F: Field> PermutationCheck<F> {
+    /// Takes `f` and `g`, and prove that their evaluations are a permutation of each other.
+    ///
+    /// The evaluations of `f` and `g` are fed into `rng` before `gamma` is sampled, so `gamma`
+    /// cannot be known before both inputs are fixed.
+    pub fn prove(
+        f_evals: &DenseMultilinearExtension<F>,
+        g_evals: &DenseMultilinearExtension<F>,
+        rng: &mut impl FeedableRNG<Error = crate::Error>,
+    ) -> Result<PermutationCheckProof<F>, crate::Error> {
+        if f_evals.num_vars != g_evals.num_vars {
+            return Err(crate::Error::OtherError(
+                "f and g have a different number of variables".into(),
+            ));
+        }
+        let gamma = Self::sample_gamma(f_evals, g_evals, rng)?;
+        let (f_product, f_proof) = GrandProduct::prove(&shift(f_evals, gamma), rng)?;
+        let (g_product, g_proof) = GrandProduct::prove(&shift(g_evals, gamma), rng)?;
+        Ok(PermutationCheckProof {
+            f_product,
+            f_proof,
+            g_product,
+            g_proof,
+        })
+    }
+
+    /// Takes `f`, `g` and the proof, and returns a subclaim about `f` and `g`.
+    ///
+    /// `f` and `g` are fed into `rng` the same way as in `prove` to derive `gamma`.
+    pub fn verify(
+        f_evals: &DenseMultilinearExtension<F>,
+        g_evals: &DenseMultilinearExtension<F>,
+        proof: &PermutationCheckProof<F>,
+        rng: &mut impl FeedableRNG<Error = crate::Error>,
+    ) -> Result<PermutationCheckSubClaim<F>, crate::Error> {
+        if f_evals.num_vars != g_evals.num_vars {
+            return Err(crate::Error::Reject(Some(
+                "f and g have a different number of variables.".into(),
+            )));
+        }
+        if proof.f_product != proof.g_product {
+            return Err(crate::Error::Reject(Some(
+                "Products of f and g do not match.".into(),
+            )));
+        }
+        let num_vars = f_evals.num_vars;
+        let gamma = Self::sample_gamma(f_evals, g_evals, rng)?;
+        let f_subclaim = GrandProduct::verify(num_vars, proof.f_product, &proof.f_proof, rng)?;
+        let g_subclaim = GrandProduct::verify(num_vars, proof.g_product, &proof.g_proof, rng)?;
+        Ok(PermutationCheckSubClaim {
+            f_point: f_subclaim.point,
+            f_expected_evaluation: f_subclaim.expected_evaluation - gamma,
+            g_point: g_subclaim.point,
+            g_expected_evaluation: g_subclaim.expected_evaluation - gamma,
+        })
+    }
+
+    fn sample_gamma(
+        f_evals: &DenseMultilinearExtension<F>,
+        g_evals: &DenseMultilinearExtension<F>,
+        rng: &mut impl FeedableRNG<Error = crate::Error>,
+    ) -> Result<F, crate::Error> {
+        rng.feed(&f_evals.evaluations)?;
+        rng.feed(&g_evals.evaluations)?;
+        Ok(F::rand(rng))
+    }
+}
+
+/// Returns the multilinear extension of `poly(x) + gamma`.
+fn shift<F: Field>(poly: &DenseMultilinearExtension<F>, gamma: F) -> DenseMultilinearExtension<F> {
+    DenseMultilinearExtension::from_evaluations_vec(
+        poly.num_vars,
+        poly.evaluations.iter().map(|v| *v + gamma).collect(),
+    )
+}
@@ -0,0 +1,76 @@
+use crate::grand_product::GrandProduct;
+use crate::permutation_check::{shift, PermutationCheck, PermutationCheckProof};
+use crate::rng::{Blake2b512Rng, FeedableRNG};
+use ark_ff::Field;
+use ark_poly::{DenseMultilinearExtension, MultilinearExtension};
+use ark_std::{test_rng, UniformRand};
+use ark_test_curves::bls12_381::Fr;
+
+#[test]
+fn test_permutation() {
+    let nv = 6;
+    let mut rng = test_rng();
+    let f = DenseMultilinearExtension::<Fr>::rand(nv, &mut rng);
+    let mut evaluations = f.evaluations.clone();
+    evaluations.reverse();
+    evaluations.swap(0, 7);
+    let g = DenseMultilinearExtension::from_evaluations_vec(nv, evaluations);
+
+    let mut fs_rng = Blake2b512Rng::setup();
+    let proof = PermutationCheck::prove(&f, &g, &mut fs_rng).expect("fail to prove");
+    let mut fs_rng = Blake2b512Rng::setup();
+    let subclaim =
+        PermutationCheck::verify(&f, &g, &proof, &mut fs_rng).expect("verification failed");
+    assert!(subclaim.verify_subclaim(&f, &g));
+}
+
+#[test]
+fn test_not_permutation() {
+    let nv = 6;
+    let mut rng = test_rng();
+    let f = DenseMultilinearExtension::<Fr>::rand(nv, &mut rng);
+    let mut evaluations = f.evaluations.clone();
+    evaluations.reverse();
+    evaluations[3] += Fr::ONE;
+    let g = DenseMultilinearExtension::from_evaluations_vec(nv, evaluations);
+
+    let mut fs_rng = Blake2b512Rng::setup();
+    let proof = PermutationCheck::prove(&f, &g, &mut fs_rng).expect("fail to prove");
+    let mut fs_rng = Blake2b512Rng::setup();
+    let result = PermutationCheck::verify(&f, &g, &proof, &mut fs_rng);
+    assert!(result.map_or(true, |subclaim| !subclaim.verify_subclaim(&f, &g)));
+}
+
+#[test]
+fn test_not_permutation_chosen_after_gamma() {
+    let nv = 6;
+    let mut rng = test_rng();
+    let f = DenseMultilinearExtension::<Fr>::rand(nv, &mut rng);
+
+    // The prover learns the gamma derived from f and a permutation of f...
+    let mut fs_rng = Blake2b512Rng::setup();
+    fs_rng.feed(&f.evaluations).unwrap();
+    fs_rng.feed(&f.evaluations).unwrap();
+    let gamma = Fr::rand(&mut fs_rng);
+
+    // ...and then picks a g that is not a permutation of f but has the same product for it.
+    let c = Fr::from(2u64);
+    let mut evaluations = f.evaluations.clone();
+    evaluations[0] = (evaluations[0] + gamma) * c - gamma;
+    evaluations[1] = (evaluations[1] + gamma) * c.inverse().unwrap() - gamma;
+    let g = DenseMultilinearExtension::from_evaluations_vec(nv, evaluations);
+
+    let (f_product, f_proof) = GrandProduct::prove(&shift(&f, gamma), &mut fs_rng).unwrap();
+    let (g_product, g_proof) = GrandProduct::prove(&shift(&g, gamma), &mut fs_rng).unwrap();
+    assert_eq!(f_product, g_product);
+    let proof = PermutationCheckProof {
+        f_product,
+        f_proof,
+        g_product,
+        g_proof,
+    };
+
+    let mut fs_rng = Blake2b512Rng::setup();
+    let result = PermutationCheck::verify(&f, &g, &proof, &mut fs_rng);
+    assert!(result.map_or(true, |subclaim| !subclaim.verify_subclaim(&f, &g)));
+}
@@ -1,8 +1,9 @@
 //! Fiat-Shamir Random Generator
+use ark_ff::Field;
 use ark_serialize::CanonicalSerialize;
 use ark_std::rand::RngCore;
 use ark_std::vec::Vec;
-use blake2::{Blake2b512, Digest};
+use blake2::{Blake2b512, Blake2s256, Digest};
 /// Random Field Element Generator where randomness `feed` adds entropy for the output.
 ///
 /// Implementation should support all types of input that has `ToBytes` trait.
@@ -16,9 +17,67 @@ pub trait FeedableRNG: RngCore {
 
     /// Provide randomness for the generator, given the message.
     fn feed<M: CanonicalSerialize>(&mut self, msg: &M) -> Result<(), Self::Error>;
+
+    /// Provide randomness for the generator, given raw bytes.
+    ///
+    /// The bytes are absorbed as they are, without the length prefix `feed` adds when serializing
+    /// a `Vec<u8>`, so `feed_bytes(bytes)` is not interchangeable with `feed(&bytes.to_vec())`.
+    /// Feeding `a` and then `b` is the same as feeding `a` concatenated with `b`.
+    fn feed_bytes(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        for byte in bytes {
+            self.feed(byte)?;
+        }
+        Ok(())
+    }
+
+    /// Provide randomness for the generator, given the message and a domain separation label.
+    ///
+    /// The length of the label and the label are fed before the message, so that different
+    /// splits of the same bytes between the label and the message yield different results.
+    fn feed_with_label<M: CanonicalSerialize>(
+        &mut self,
+        label: &'static [u8],
+        msg: &M,
+    ) -> Result<(), Self::Error> {
+        self.feed(&label.to_vec())?;
+        self.feed(msg)
+    }
+
+    /// Derive an independent generator from the current state and `index`, leaving `self`
+    /// untouched.
+    ///
+    /// Forks with the same index produce the same stream, and forks with different indices
+    /// diverge.
+    fn fork(&self, index: u64) -> Self
+    where
+        Self: Clone,
+    {
+        let mut forked = self.clone();
+        forked
+            .feed_with_label(b"fork", &index)
+            .expect("fail to feed the fork index");
+        forked
+    }
 }
 
+/// Extension of `RngCore` for sampling several field elements at once.
+pub trait SampleFieldElements: RngCore {
+    /// Sample `n` field elements.
+    ///
+    /// The output is the same as calling `F::rand` `n` times on the same generator, so switching
+    /// to this method does not change any transcript.
+    fn sample_field_elements<F: Field>(&mut self, n: usize) -> Vec<F>
+    where
+        Self: Sized,
+    {
+        (0..n).map(|_| F::rand(self)).collect()
+    }
+}
+
+impl<R: RngCore> SampleFieldElements for R {}
+
 /// 512-bits digest hash pseudorandom generator
+#[derive(Clone)]
 pub struct Blake2b512Rng {
     /// current digest instance
     current_digest: Blake2b512,
@@ -39,6 +98,23 @@ impl FeedableRNG for Blake2b512Rng {
         self.current_digest.update(&buf);
         Ok(())
     }
+
+    fn feed_bytes(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.current_digest.update(bytes);
+        Ok(())
+    }
+
+    fn feed_with_label<M: CanonicalSerialize>(
+        &mut self,
+        label: &'static [u8],
+        msg: &M,
+    ) -> Result<(), Self::Error> {
+        // same bytes as feeding `label.to_vec()`, without the copy
+        self.current_digest
+            .update((label.len() as u64).to_le_bytes());
+        self.current_digest.update(label);
+        self.feed(msg)
+    }
 }
 
 impl RngCore for Blake2b512Rng {
@@ -59,37 +135,95 @@ impl RngCore for Blake2b512Rng {
     }
 
     fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), ark_std::rand::Error> {
-        let mut digest = self.current_digest.clone();
-        let mut output = digest.finalize();
-        let output_size = Blake2b512::output_size();
-        let mut ptr = 0;
-        let mut digest_ptr = 0;
-        while ptr < dest.len() {
-            dest[ptr] = output[digest_ptr];
-            ptr += 1usize;
-            digest_ptr += 1;
-            if digest_ptr == output_size {
-                self.current_digest.update(output);
-                digest = self.current_digest.clone();
-                output = digest.finalize();
-                digest_ptr = 0;
-            }
+        fill_bytes_from_digest(&mut self.current_digest, dest);
+        Ok(())
+    }
+}
+
+/// 256-bits digest hash pseudorandom generator
+#[derive(Clone)]
+pub struct Blake2s256Rng {
+    /// current digest instance
+    current_digest: Blake2s256,
+}
+
+impl FeedableRNG for Blake2s256Rng {
+    type Error = crate::Error;
+
+    fn setup() -> Self {
+        Self {
+            current_digest: Blake2s256::new(),
         }
-        self.current_digest.update(output);
+    }
+
+    fn feed<M: CanonicalSerialize>(&mut self, msg: &M) -> Result<(), Self::Error> {
+        let mut buf = Vec::new();
+        msg.serialize_uncompressed(&mut buf)?;
+        self.current_digest.update(&buf);
+        Ok(())
+    }
+
+    fn feed_bytes(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.current_digest.update(bytes);
+        Ok(())
+    }
+}
+
+impl RngCore for Blake2s256Rng {
+    fn next_u32(&mut self) -> u32 {
+        let mut temp = [0u8; 4];
+        self.fill_bytes(&mut temp);
+        u32::from_le_bytes(temp)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut temp = [0u8; 8];
+        self.fill_bytes(&mut temp);
+        u64::from_le_bytes(temp)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), ark_std::rand::Error> {
+        fill_bytes_from_digest(&mut self.current_digest, dest);
         Ok(())
     }
 }
 
+/// Fill `dest` with the output of `current_digest`. Every time a whole digest output is consumed,
+/// and once at the end, the output is fed back into `current_digest`.
+fn fill_bytes_from_digest<D: Digest + Clone>(current_digest: &mut D, dest: &mut [u8]) {
+    let mut digest = current_digest.clone();
+    let mut output = digest.finalize();
+    let output_size = <D as Digest>::output_size();
+    let mut ptr = 0;
+    let mut digest_ptr = 0;
+    while ptr < dest.len() {
+        dest[ptr] = output[digest_ptr];
+        ptr += 1usize;
+        digest_ptr += 1;
+        if digest_ptr == output_size {
+            current_digest.update(&output);
+            digest = current_digest.clone();
+            output = digest.finalize();
+            digest_ptr = 0;
+        }
+    }
+    current_digest.update(&output);
+}
+
 #[cfg(test)]
 mod tests {
     use ark_ff::Field;
     use ark_std::rand::Rng;
     use ark_std::rand::RngCore;
 
-    use crate::rng::{Blake2b512Rng, FeedableRNG};
+    use crate::rng::{Blake2b512Rng, Blake2s256Rng, FeedableRNG, SampleFieldElements};
     use ark_serialize::CanonicalSerialize;
-    use ark_std::test_rng;
     use ark_std::vec::Vec;
+    use ark_std::{test_rng, UniformRand};
     use ark_test_curves::bls12_381::Fr;
 
     /// Special type of input used for test.
@@ -173,4 +307,102 @@ mod tests {
     fn test_blake2s_hashing() {
         test_deterministic_pseudorandom_generator::<Blake2b512Rng, Fr>(5)
     }
+
+    #[test]
+    fn test_blake2s256_hashing() {
+        test_deterministic_pseudorandom_generator::<Blake2s256Rng, Fr>(5)
+    }
+
+    #[test]
+    fn test_feed_with_label() {
+        let mut rng = test_rng();
+        let msg = TestMessage::rand(&mut rng, 32);
+
+        let mut rng_a = Blake2b512Rng::setup();
+        rng_a.feed_with_label(b"a", &msg).unwrap();
+        let mut rng_b = Blake2b512Rng::setup();
+        rng_b.feed_with_label(b"b", &msg).unwrap();
+        assert_ne!(Fr::rand(&mut rng_a), Fr::rand(&mut rng_b));
+
+        // label and message boundaries are not ambiguous
+        let mut rng_a = Blake2b512Rng::setup();
+        rng_a.feed_with_label(b"ab", &()).unwrap();
+        let mut rng_b = Blake2b512Rng::setup();
+        rng_b.feed_with_label(b"a", &b'b').unwrap();
+        assert_ne!(Fr::rand(&mut rng_a), Fr::rand(&mut rng_b));
+
+        // the override agrees with the default implementation
+        let mut rng_a = Blake2b512Rng::setup();
+        rng_a.feed_with_label(b"label", &msg).unwrap();
+        let mut rng_b = Blake2b512Rng::setup();
+        rng_b.feed(&b"label".to_vec()).unwrap();
+        rng_b.feed(&msg).unwrap();
+        assert_eq!(Fr::rand(&mut rng_a), Fr::rand(&mut rng_b));
+    }
+
+    #[test]
+    fn test_fork() {
+        let mut rng = test_rng();
+        let msg = TestMessage::rand(&mut rng, 32);
+        let mut parent = Blake2b512Rng::setup();
+        parent.feed(&msg).unwrap();
+
+        let mut fork_0 = parent.fork(0);
+        let mut fork_1 = parent.fork(1);
+        let mut another_fork_0 = parent.fork(0);
+        let output_0: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut fork_0)).collect();
+        let output_1: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut fork_1)).collect();
+        let another_output_0: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut another_fork_0)).collect();
+        assert_eq!(output_0, another_output_0);
+        assert_ne!(output_0, output_1);
+
+        // the parent is untouched
+        let mut another_parent = Blake2b512Rng::setup();
+        another_parent.feed(&msg).unwrap();
+        assert_eq!(Fr::rand(&mut parent), Fr::rand(&mut another_parent));
+    }
+
+    #[test]
+    fn test_sample_field_elements() {
+        let mut rng = test_rng();
+        let msg = TestMessage::rand(&mut rng, 32);
+        let mut rng_a = Blake2b512Rng::setup();
+        rng_a.feed(&msg).unwrap();
+        let mut rng_b = Blake2b512Rng::setup();
+        rng_b.feed(&msg).unwrap();
+
+        let bulk: Vec<Fr> = rng_a.sample_field_elements(16);
+        let looped: Vec<Fr> = (0..16).map(|_| Fr::rand(&mut rng_b)).collect();
+        assert_eq!(bulk, looped);
+        assert_eq!(Fr::rand(&mut rng_a), Fr::rand(&mut rng_b));
+    }
+
+    #[test]
+    fn test_feed_bytes() {
+        let mut rng = test_rng();
+        let msg = TestMessage::rand(&mut rng, 64);
+
+        let mut rng_a = Blake2b512Rng::setup();
+        rng_a.feed_bytes(&msg.data).unwrap();
+        let mut rng_b = Blake2b512Rng::setup();
+        rng_b.feed_bytes(&msg.data[..20]).unwrap();
+        rng_b.feed_bytes(&msg.data[20..]).unwrap();
+        assert_eq!(Fr::rand(&mut rng_a), Fr::rand(&mut rng_b));
+
+        // same as feeding each byte
+        let mut rng_c = Blake2b512Rng::setup();
+        rng_c.feed_bytes(&msg.data).unwrap();
+        let mut rng_d = Blake2b512Rng::setup();
+        for byte in &msg.data {
+            rng_d.feed(byte).unwrap();
+        }
+        assert_eq!(Fr::rand(&mut rng_c), Fr::rand(&mut rng_d));
+
+        // different framing than `feed`
+        let mut rng_e = Blake2b512Rng::setup();
+        rng_e.feed_bytes(&msg.data).unwrap();
+        let mut rng_f = Blake2b512Rng::setup();
+        rng_f.feed(&msg.data).unwrap();
+        assert_ne!(Fr::rand(&mut rng_e), Fr::rand(&mut rng_f));
+    }
 }
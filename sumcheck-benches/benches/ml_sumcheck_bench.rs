@@ -2,7 +2,7 @@
 extern crate criterion;
 
 use ark_ff::Field;
-use ark_linear_sumcheck::ml_sumcheck::protocol::ListOfProductsOfPolynomials;
+use ark_linear_sumcheck::ml_sumcheck::protocol::{IPForMLSumcheck, ListOfProductsOfPolynomials};
 use ark_linear_sumcheck::ml_sumcheck::MLSumcheck;
 use ark_poly::{DenseMultilinearExtension, MultilinearExtension};
 use ark_std::ops::Range;
@@ -61,8 +61,32 @@ fn verify_bench<F: Field>(c: &mut Criterion) {
     }
 }
 
+fn prove_product_of_two_bench<F: Field>(c: &mut Criterion) {
+    let mut rng = test_rng();
+
+    let mut group = c.benchmark_group("Prove product of two");
+    for nv in NUM_VARIABLES_RANGE {
+        let f = DenseMultilinearExtension::<F>::rand(nv, &mut rng);
+        let g = DenseMultilinearExtension::<F>::rand(nv, &mut rng);
+        group.bench_with_input(BenchmarkId::new("ML", nv), &nv, |b, &nv| {
+            let mut products = ListOfProductsOfPolynomials::new(nv);
+            products.add_product(vec![Rc::new(f.clone()), Rc::new(g.clone())], F::one());
+            b.iter(|| MLSumcheck::prove(black_box(&products)));
+        });
+        group.bench_with_input(BenchmarkId::new("product_of_two", nv), &nv, |b, _| {
+            b.iter(|| {
+                IPForMLSumcheck::prove_product_of_two(
+                    black_box(&f.evaluations),
+                    black_box(&g.evaluations),
+                )
+            });
+        });
+    }
+}
+
 fn bench_bls_381(c: &mut Criterion) {
     prove_bench::<ark_test_curves::bls12_381::Fr>(c);
+    prove_product_of_two_bench::<ark_test_curves::bls12_381::Fr>(c);
     verify_bench::<ark_test_curves::bls12_381::Fr>(c);
 }
 